@@ -1,8 +1,15 @@
-use std::{error::Error, str::FromStr};
+use std::{io::BufRead, str::FromStr};
 
+use chrono::{DateTime, Datelike, Duration, NaiveDateTime, Utc};
 use serde::{de, ser};
 
-#[derive(Debug, Copy, Clone)]
+use crate::{
+    geo,
+    hurdat2::{Status, Storm, TrackEntry, WindRadii},
+    Error, ValueError,
+};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub enum Basin {
     NorthAtlantic,
     CentralNorthPacific,
@@ -23,19 +30,131 @@ impl Basin {
             Basin::SouthernHemisphere => "SH",
         }
     }
+
+    // containing returns the basin whose waters include loc, regardless of
+    // the basin a storm there would be numbered in. The basins cover the
+    // globe, so this is only None at the north pole or for latitudes beyond
+    // it. See boundary for how the basins are divided.
+    pub fn containing(loc: &geo::Location) -> Option<Basin> {
+        let lng = (loc.lng() + 180.0).rem_euclid(360.0) - 180.0;
+        BASINS
+            .iter()
+            .copied()
+            .find(|b| polygon_contains(b.boundary(), loc.lat(), lng))
+    }
+
+    // boundary returns the polygon that bounds the basin, as (lat, lng)
+    // vertices with longitudes in [-180, 180]. The Atlantic and eastern
+    // Pacific are divided along the Central American isthmus and the
+    // continental divide of North America, the Pacific basins at 140W and
+    // 180 and the western Pacific and North Indian at 100E. The Atlantic
+    // extends east to 40E so that the extratropical tracks of its storms
+    // across Europe stay within it.
+    pub fn boundary(&self) -> &'static [(f64, f64)] {
+        match self {
+            Basin::NorthAtlantic => &NORTH_ATLANTIC,
+            Basin::CentralNorthPacific => &CENTRAL_NORTH_PACIFIC,
+            Basin::EasternNorthPacific => &EASTERN_NORTH_PACIFIC,
+            Basin::WesternNorthPacific => &WESTERN_NORTH_PACIFIC,
+            Basin::NorthIndian => &NORTH_INDIAN,
+            Basin::SouthernHemisphere => &SOUTHERN_HEMISPHERE,
+        }
+    }
+}
+
+const BASINS: [Basin; 6] = [
+    Basin::NorthAtlantic,
+    Basin::CentralNorthPacific,
+    Basin::EasternNorthPacific,
+    Basin::WesternNorthPacific,
+    Basin::NorthIndian,
+    Basin::SouthernHemisphere,
+];
+
+// AMERICAS_DIVIDE separates the Atlantic from the eastern Pacific, running
+// from the equator west of Colombia up the isthmus, across Tehuantepec and
+// along the continental divide to the pole.
+const AMERICAS_DIVIDE: [(f64, f64); 18] = [
+    (0.0, -78.0),
+    (7.5, -77.5),
+    (9.0, -79.5),
+    (8.5, -82.5),
+    (10.5, -84.0),
+    (11.2, -85.5),
+    (13.0, -86.8),
+    (14.5, -88.5),
+    (15.0, -91.0),
+    (17.0, -94.7),
+    (20.0, -99.0),
+    (25.0, -103.0),
+    (31.0, -108.0),
+    (37.0, -107.0),
+    (45.0, -111.0),
+    (49.0, -114.0),
+    (60.0, -130.0),
+    (90.0, -140.0),
+];
+
+const NORTH_ATLANTIC: [(f64, f64); 20] = beside_divide((90.0, 40.0), (0.0, 40.0));
+
+const EASTERN_NORTH_PACIFIC: [(f64, f64); 20] = beside_divide((90.0, -140.0), (0.0, -140.0));
+
+// beside_divide returns the polygon made by closing AMERICAS_DIVIDE through
+// the corners at the pole and the equator.
+const fn beside_divide(pole: (f64, f64), equator: (f64, f64)) -> [(f64, f64); 20] {
+    let mut b = [(0.0, 0.0); 20];
+    let mut i = 0;
+    while i < AMERICAS_DIVIDE.len() {
+        b[i] = AMERICAS_DIVIDE[i];
+        i += 1;
+    }
+    b[18] = pole;
+    b[19] = equator;
+    b
+}
+
+const CENTRAL_NORTH_PACIFIC: [(f64, f64); 4] =
+    [(0.0, -180.0), (90.0, -180.0), (90.0, -140.0), (0.0, -140.0)];
+
+const WESTERN_NORTH_PACIFIC: [(f64, f64); 4] =
+    [(0.0, 100.0), (90.0, 100.0), (90.0, 180.0), (0.0, 180.0)];
+
+const NORTH_INDIAN: [(f64, f64); 4] = [(0.0, 40.0), (90.0, 40.0), (90.0, 100.0), (0.0, 100.0)];
+
+const SOUTHERN_HEMISPHERE: [(f64, f64); 4] =
+    [(-90.0, -180.0), (0.0, -180.0), (0.0, 180.0), (-90.0, 180.0)];
+
+// polygon_contains reports whether the point is inside the polygon by
+// counting the edges that a ray cast east from it crosses. Points on an
+// edge shared by two basins are counted in exactly one of them: the one to
+// the north of a parallel and to the east of a meridian.
+fn polygon_contains(polygon: &[(f64, f64)], lat: f64, lng: f64) -> bool {
+    let mut inside = false;
+    let mut j = polygon.len() - 1;
+    for (i, &(lat_i, lng_i)) in polygon.iter().enumerate() {
+        let (lat_j, lng_j) = polygon[j];
+        if (lat_i > lat) != (lat_j > lat)
+            && lng < lng_i + (lat - lat_i) * (lng_j - lng_i) / (lat_j - lat_i)
+        {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
 }
 
 impl FromStr for Basin {
-    type Err = Box<dyn Error>;
+    type Err = ValueError;
+
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s {
+        match s.to_ascii_uppercase().as_str() {
             "AL" => Ok(Basin::NorthAtlantic),
             "CP" => Ok(Basin::CentralNorthPacific),
             "EP" => Ok(Basin::EasternNorthPacific),
             "WP" => Ok(Basin::WesternNorthPacific),
             "IO" => Ok(Basin::NorthIndian),
             "SH" => Ok(Basin::SouthernHemisphere),
-            _ => Err(format!("invalid basin: {}", s).into()),
+            _ => Err(ValueError::new("basin", s)),
         }
     }
 }
@@ -46,25 +165,192 @@ impl std::fmt::Display for Basin {
     }
 }
 
-#[derive(Debug)]
+impl ser::Serialize for Basin {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_str(self.to_str())
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Basin {
+    fn deserialize<D>(d: D) -> Result<Basin, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let s = String::deserialize(d)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+// SubBasin is the region of a basin that JTWC appends to the numbers of
+// storms in the north Indian Ocean and southern hemisphere, e.g. 02B.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum SubBasin {
+    ArabianSea,
+    BayOfBengal,
+    SouthIndian,
+    SouthPacific,
+}
+
+impl SubBasin {
+    pub fn to_char(&self) -> char {
+        match self {
+            SubBasin::ArabianSea => 'A',
+            SubBasin::BayOfBengal => 'B',
+            SubBasin::SouthIndian => 'S',
+            SubBasin::SouthPacific => 'P',
+        }
+    }
+
+    pub fn from_char(c: char) -> Result<SubBasin, ValueError> {
+        match c.to_ascii_uppercase() {
+            'A' => Ok(SubBasin::ArabianSea),
+            'B' => Ok(SubBasin::BayOfBengal),
+            'S' => Ok(SubBasin::SouthIndian),
+            'P' => Ok(SubBasin::SouthPacific),
+            _ => Err(ValueError::new("sub-basin", &c.to_string())),
+        }
+    }
+
+    // basin returns the basin that contains the sub-basin.
+    pub fn basin(&self) -> Basin {
+        match self {
+            SubBasin::ArabianSea | SubBasin::BayOfBengal => Basin::NorthIndian,
+            SubBasin::SouthIndian | SubBasin::SouthPacific => Basin::SouthernHemisphere,
+        }
+    }
+}
+
+impl std::fmt::Display for SubBasin {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_char())
+    }
+}
+
+// Id identifies a storm by its basin, its number within the basin's season
+// and the year. Ids order chronologically, by year and then number. Storms
+// numbered 90 to 99 are invests, disturbances that are being watched but
+// aren't (yet) cyclones.
+//
+// An id may also carry the sub-basin the storm formed in, which is not part
+// of its identity: ids that differ only by sub-basin are equal.
+#[derive(Debug, Clone)]
 pub struct Id {
     basin: Basin,
     number: i32,
     year: i32,
+    sub_basin: Option<SubBasin>,
 }
 
+impl Id {
+    pub fn new(basin: Basin, number: i32, year: i32) -> Id {
+        Id {
+            basin,
+            number,
+            year,
+            sub_basin: None,
+        }
+    }
+
+    pub fn with_sub_basin(mut self, sub_basin: Option<SubBasin>) -> Id {
+        self.sub_basin = sub_basin;
+        self
+    }
+
+    pub fn basin(&self) -> Basin {
+        self.basin
+    }
+
+    pub fn number(&self) -> i32 {
+        self.number
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn sub_basin(&self) -> Option<SubBasin> {
+        self.sub_basin
+    }
+
+    pub fn is_invest(&self) -> bool {
+        (90..=99).contains(&self.number)
+    }
+
+    // designator returns the short form JTWC uses for the storm, which is
+    // its number followed by the sub-basin, or the basin's initial when the
+    // sub-basin isn't known, e.g. 02B or 09L.
+    pub fn designator(&self) -> String {
+        let suffix = match (self.sub_basin, self.basin) {
+            (Some(sub_basin), _) => sub_basin.to_char(),
+            (None, Basin::NorthAtlantic) => 'L',
+            (None, Basin::CentralNorthPacific) => 'C',
+            (None, Basin::EasternNorthPacific) => 'E',
+            (None, Basin::WesternNorthPacific) => 'W',
+            (None, Basin::NorthIndian) => 'I',
+            (None, Basin::SouthernHemisphere) => 'S',
+        };
+        format!("{:02}{}", self.number, suffix)
+    }
+
+    fn key(&self) -> (i32, i32, Basin) {
+        (self.year, self.number, self.basin)
+    }
+}
+
+impl PartialEq for Id {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Id {}
+
+impl PartialOrd for Id {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Id {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl std::hash::Hash for Id {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+
+// Ids are parsed, ignoring case, from either the full form used by HURDAT2,
+// e.g. AL092023, or the form with a two digit year used in older ATCF files,
+// e.g. AL0923. Two digit years from 50 on are in the 1900s.
 impl FromStr for Id {
-    type Err = Box<dyn Error>;
+    type Err = ValueError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 8 {
-            return Err("atcf id must be 8 characters".into());
+        let invalid = || ValueError::new("atcf id", s);
+        if !s.is_ascii() || !matches!(s.len(), 6 | 8) || !s[2..].bytes().all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid());
         }
-        Ok(Id {
-            basin: s[..2].parse()?,
-            number: s[2..4].parse()?,
-            year: s[4..8].parse()?,
-        })
+        let year = match s.len() {
+            8 => s[4..8].parse().map_err(|_| invalid())?,
+            6 => match s[4..6].parse::<i32>().map_err(|_| invalid())? {
+                yy if yy >= 50 => 1900 + yy,
+                yy => 2000 + yy,
+            },
+            _ => return Err(invalid()),
+        };
+        Ok(Id::new(
+            s[..2].parse()?,
+            s[2..4].parse().map_err(|_| invalid())?,
+            year,
+        ))
     }
 }
 
@@ -93,3 +379,531 @@ impl<'de> de::Deserialize<'de> for Id {
         s.parse().map_err(de::Error::custom)
     }
 }
+
+// Record is a single line of an ATCF deck. A-decks hold forecasts and b-decks
+// the operational best track, but both use the same comma separated columns.
+// Only the columns cat5 uses are kept. Values that ATCF writes as 0 when they
+// are unknown, e.g. pressure or the radius of maximum wind, are None.
+#[derive(Debug, Clone)]
+pub struct Record {
+    basin: Basin,
+    number: i32,
+    time: DateTime<Utc>,
+    technique_number: Option<i32>,
+    technique: String,
+    tau: i32,
+    location: Option<geo::Location>,
+    max_wind: Option<i32>,
+    min_pressure: Option<i32>,
+    status: Option<String>,
+    radii_kts: Option<i32>,
+    radii: Option<WindRadii>,
+    outer_pressure: Option<i32>,
+    outer_radius: Option<i32>,
+    radius_of_max_wind: Option<i32>,
+    gusts: Option<i32>,
+    eye_diameter: Option<i32>,
+    sub_basin: Option<SubBasin>,
+    name: Option<String>,
+}
+
+impl Record {
+    pub fn basin(&self) -> Basin {
+        self.basin
+    }
+
+    pub fn number(&self) -> i32 {
+        self.number
+    }
+
+    // time is the synoptic time of the record, which is the initial time of
+    // a forecast.
+    pub fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
+
+    // technique_number is the sorting number of an a-deck technique. In
+    // b-decks it holds the minutes past the hour of a fix.
+    pub fn technique_number(&self) -> Option<i32> {
+        self.technique_number
+    }
+
+    // technique is the name of the aid, e.g. OFCL or GFSI, or BEST.
+    pub fn technique(&self) -> &str {
+        &self.technique
+    }
+
+    // tau is the forecast lead time in hours.
+    pub fn tau(&self) -> i32 {
+        self.tau
+    }
+
+    pub fn location(&self) -> Option<&geo::Location> {
+        self.location.as_ref()
+    }
+
+    pub fn max_wind(&self) -> Option<i32> {
+        self.max_wind
+    }
+
+    pub fn min_pressure(&self) -> Option<i32> {
+        self.min_pressure
+    }
+
+    // status is the ATCF system depth code, e.g. TS, HU or TY.
+    pub fn status(&self) -> Option<&str> {
+        self.status.as_deref()
+    }
+
+    // radii_kts is the wind speed (34, 50 or 64) that radii gives the extent
+    // of.
+    pub fn radii_kts(&self) -> Option<i32> {
+        self.radii_kts
+    }
+
+    pub fn radii(&self) -> Option<&WindRadii> {
+        self.radii.as_ref()
+    }
+
+    // outer_pressure is the pressure of the last closed isobar, in mb.
+    pub fn outer_pressure(&self) -> Option<i32> {
+        self.outer_pressure
+    }
+
+    // outer_radius is the radius of the last closed isobar, in nm.
+    pub fn outer_radius(&self) -> Option<i32> {
+        self.outer_radius
+    }
+
+    pub fn radius_of_max_wind(&self) -> Option<i32> {
+        self.radius_of_max_wind
+    }
+
+    pub fn gusts(&self) -> Option<i32> {
+        self.gusts
+    }
+
+    pub fn eye_diameter(&self) -> Option<i32> {
+        self.eye_diameter
+    }
+
+    // sub_basin is the sub-basin the storm was in at the time of the record,
+    // if it is in one of the basins that JTWC divides.
+    pub fn sub_basin(&self) -> Option<SubBasin> {
+        self.sub_basin
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+impl FromStr for Record {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ValueError::new("atcf record", s);
+        let fields = s.split(',').map(str::trim).collect::<Vec<_>>();
+        if fields.len() < 8 {
+            return Err(invalid());
+        }
+        let field = |i: usize| fields.get(i).copied().filter(|v| !v.is_empty());
+        let int = |i: usize| field(i).and_then(|v| v.parse::<i32>().ok());
+        let known = |i: usize| int(i).filter(|&v| v != 0);
+
+        let time = NaiveDateTime::parse_from_str(&format!("{}00", fields[2]), "%Y%m%d%H%M")
+            .map_err(|_| invalid())?
+            .and_utc();
+        let location = match (parse_tenths(fields[6]), parse_tenths(fields[7])) {
+            (Some(lat), Some(lng)) if lat != 0.0 || lng != 0.0 => {
+                Some(geo::Location::new(lat, lng))
+            }
+            _ => None,
+        };
+        let radii_kts = known(11);
+        let radii = match (radii_kts, field(12)) {
+            (Some(_), Some(code)) => {
+                let r = |i| int(i);
+                match code {
+                    "AAA" => Some(WindRadii::new(r(13), r(13), r(13), r(13))),
+                    "NEQ" => Some(WindRadii::new(r(13), r(14), r(15), r(16))),
+                    "NNS" => Some(WindRadii::new(r(13), r(14), r(14), r(13))),
+                    "EES" => Some(WindRadii::new(r(13), r(13), r(14), r(14))),
+                    _ => None,
+                }
+            }
+            _ => None,
+        };
+
+        Ok(Record {
+            basin: fields[0].parse()?,
+            number: fields[1].parse().map_err(|_| invalid())?,
+            time,
+            technique_number: int(3),
+            technique: fields[4].to_owned(),
+            tau: int(5).unwrap_or(0),
+            location,
+            max_wind: known(8),
+            min_pressure: known(9),
+            status: field(10).map(str::to_owned),
+            radii_kts,
+            radii,
+            outer_pressure: known(17),
+            outer_radius: known(18),
+            radius_of_max_wind: known(19),
+            gusts: known(20),
+            eye_diameter: known(21),
+            sub_basin: field(22)
+                .and_then(|v| v.chars().next())
+                .and_then(|c| SubBasin::from_char(c).ok()),
+            name: field(27).map(str::to_owned),
+        })
+    }
+}
+
+// parse_tenths parses an ATCF latitude or longitude, which is in tenths of a
+// degree followed by a hemisphere, e.g. 154N or 835W.
+fn parse_tenths(s: &str) -> Option<f64> {
+    let (v, hemisphere) = s.split_at(s.len().checked_sub(1)?);
+    let v = v.parse::<f64>().ok()? / 10.0;
+    match hemisphere {
+        "N" | "n" | "E" | "e" => Some(v),
+        "S" | "s" | "W" | "w" => Some(-v),
+        _ => None,
+    }
+}
+
+// read_records reads the records in an ATCF deck, skipping blank lines.
+pub fn read_records<R: BufRead>(r: R) -> Result<Vec<Record>, Error> {
+    let mut records = vec![];
+    for line in r.lines() {
+        let line = line?;
+        if !line.trim().is_empty() {
+            records.push(line.parse()?);
+        }
+    }
+    Ok(records)
+}
+
+// is_deck returns true if contents look like the start of an ATCF deck,
+// which begins with a two letter basin followed by a comma.
+pub fn is_deck(contents: &[u8]) -> bool {
+    contents.len() >= 3 && contents[..2].iter().all(u8::is_ascii_alphabetic) && contents[2] == b','
+}
+
+// Fix is a storm's position and intensity at one time of an ATCF best track.
+// A b-deck writes a line for each of the 34, 50 and 64 kt wind radii at a
+// time, which are combined into a single fix.
+#[derive(Debug, Clone)]
+pub struct Fix {
+    time: DateTime<Utc>,
+    status: Status,
+    location: geo::Location,
+    max_wind: i32,
+    min_pressure: Option<i32>,
+    wind_radii: [Option<WindRadii>; 3],
+    outer_pressure: Option<i32>,
+    outer_radius: Option<i32>,
+    radius_of_max_wind: Option<i32>,
+    gusts: Option<i32>,
+    eye_diameter: Option<i32>,
+}
+
+impl Fix {
+    fn from_record(r: &Record, location: geo::Location) -> Fix {
+        let max_wind = r.max_wind.unwrap_or(0);
+        Fix {
+            time: r.time + Duration::minutes(r.technique_number.unwrap_or(0) as i64),
+            status: status_from_code(r.status(), max_wind),
+            location,
+            max_wind,
+            min_pressure: r.min_pressure,
+            wind_radii: [None, None, None],
+            outer_pressure: r.outer_pressure,
+            outer_radius: r.outer_radius,
+            radius_of_max_wind: r.radius_of_max_wind,
+            gusts: r.gusts,
+            eye_diameter: r.eye_diameter,
+        }
+    }
+
+    pub fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn location(&self) -> &geo::Location {
+        &self.location
+    }
+
+    pub fn max_wind(&self) -> i32 {
+        self.max_wind
+    }
+
+    pub fn min_pressure(&self) -> Option<i32> {
+        self.min_pressure
+    }
+
+    // wind_radii returns the extent of the 34, 50 or 64 kt winds.
+    pub fn wind_radii(&self, kts: i32) -> Option<&WindRadii> {
+        match kts {
+            34 => self.wind_radii[0].as_ref(),
+            50 => self.wind_radii[1].as_ref(),
+            64 => self.wind_radii[2].as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn outer_pressure(&self) -> Option<i32> {
+        self.outer_pressure
+    }
+
+    pub fn outer_radius(&self) -> Option<i32> {
+        self.outer_radius
+    }
+
+    pub fn radius_of_max_wind(&self) -> Option<i32> {
+        self.radius_of_max_wind
+    }
+
+    pub fn gusts(&self) -> Option<i32> {
+        self.gusts
+    }
+
+    pub fn eye_diameter(&self) -> Option<i32> {
+        self.eye_diameter
+    }
+
+    fn to_track_entry(&self) -> TrackEntry {
+        let mut entry =
+            TrackEntry::new(self.time, self.status, self.location.clone(), self.max_wind)
+                .with_min_pressure(self.min_pressure)
+                .with_radius_of_max_wind(self.radius_of_max_wind);
+        for (kts, radii) in [34, 50, 64].into_iter().zip(&self.wind_radii) {
+            if let Some(radii) = radii {
+                entry = entry.with_wind_radii(kts, radii.clone());
+            }
+        }
+        entry
+    }
+}
+
+// status_from_code maps an ATCF system depth code onto the smaller set of
+// statuses used by HURDAT2. Typhoons and super typhoons are hurricanes, and
+// codes that say nothing about intensity are decided by the wind.
+fn status_from_code(code: Option<&str>, wind: i32) -> Status {
+    match code.map(|c| c.to_ascii_uppercase()).as_deref() {
+        Some(code @ ("TD" | "TS" | "HU" | "EX" | "SD" | "SS" | "LO" | "WV" | "DB")) => {
+            code.parse().unwrap()
+        }
+        Some("TY" | "ST") => Status::Hurricane,
+        Some("ET") => Status::Extratropical,
+        Some("PT") => Status::Low,
+        _ if wind >= 64 => Status::Hurricane,
+        _ if wind >= 34 => Status::TropicalStorm,
+        _ => Status::TropicalDepression,
+    }
+}
+
+// BestTrack is the operational best track of a storm, as read from an ATCF
+// b-deck such as bal092023.dat.
+#[derive(Debug, Clone)]
+pub struct BestTrack {
+    id: Id,
+    name: Option<String>,
+    fixes: Vec<Fix>,
+}
+
+impl BestTrack {
+    // read reads a b-deck. Lines for techniques other than BEST and those
+    // without a position are ignored.
+    pub fn read<R: BufRead>(r: R) -> Result<BestTrack, Error> {
+        Self::from_records(&read_records(r)?)
+    }
+
+    pub fn from_records(records: &[Record]) -> Result<BestTrack, Error> {
+        let mut fixes: Vec<Fix> = vec![];
+        let mut id = None;
+        let mut name = None;
+        for r in records.iter().filter(|r| r.technique == "BEST") {
+            let location = match &r.location {
+                Some(location) => location.clone(),
+                None => continue,
+            };
+            id.get_or_insert_with(|| Id::new(r.basin, r.number, 0).with_sub_basin(r.sub_basin));
+            if let Some(n) = r.name() {
+                name = Some(n.to_owned());
+            }
+
+            let fix = Fix::from_record(r, location);
+            let i = match fixes.iter().rposition(|f| f.time == fix.time) {
+                Some(i) => i,
+                None => {
+                    fixes.push(fix);
+                    fixes.len() - 1
+                }
+            };
+            if let (Some(kts), Some(radii)) = (r.radii_kts, &r.radii) {
+                match kts {
+                    34 => fixes[i].wind_radii[0] = Some(radii.clone()),
+                    50 => fixes[i].wind_radii[1] = Some(radii.clone()),
+                    64 => fixes[i].wind_radii[2] = Some(radii.clone()),
+                    _ => {}
+                }
+            }
+        }
+        fixes.sort_by_key(|f| f.time);
+
+        // The year of the id is the year the storm formed in.
+        let id = match (id, fixes.first()) {
+            (Some(id), Some(first)) => {
+                Id::new(id.basin, id.number, first.time.year()).with_sub_basin(id.sub_basin)
+            }
+            _ => return Err(ValueError::new("b-deck", "no best track fixes").into()),
+        };
+        Ok(BestTrack {
+            id,
+            name: name.filter(|n| n != "INVEST"),
+            fixes,
+        })
+    }
+
+    pub fn id(&self) -> &Id {
+        &self.id
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn fixes(&self) -> &[Fix] {
+        &self.fixes
+    }
+
+    // to_storm converts the best track into the HURDAT2 model, dropping the
+    // values that HURDAT2 has no place for.
+    pub fn to_storm(&self) -> Storm {
+        Storm::new(
+            self.id.clone(),
+            Some(self.name.as_deref().unwrap_or("UNNAMED").to_owned()),
+            self.fixes.iter().map(Fix::to_track_entry).collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::{Basin, BestTrack, Id, SubBasin};
+    use crate::{geo::Location, hurdat2::Status};
+
+    #[test]
+    fn containing() {
+        let basin = |lat, lng| Basin::containing(&Location::new(lat, lng));
+        assert_eq!(basin(25.0, -80.0), Some(Basin::NorthAtlantic));
+        assert_eq!(basin(15.0, -85.0), Some(Basin::NorthAtlantic));
+        assert_eq!(basin(50.0, 10.0), Some(Basin::NorthAtlantic));
+        assert_eq!(basin(12.0, -89.0), Some(Basin::EasternNorthPacific));
+        assert_eq!(basin(30.0, -115.0), Some(Basin::EasternNorthPacific));
+        assert_eq!(basin(20.0, -155.0), Some(Basin::CentralNorthPacific));
+        assert_eq!(basin(20.0, 180.0), Some(Basin::CentralNorthPacific));
+        assert_eq!(basin(20.0, 181.0), Some(Basin::CentralNorthPacific));
+        assert_eq!(basin(15.0, 130.0), Some(Basin::WesternNorthPacific));
+        assert_eq!(basin(15.0, 88.0), Some(Basin::NorthIndian));
+        assert_eq!(basin(-15.0, 60.0), Some(Basin::SouthernHemisphere));
+        assert_eq!(basin(0.0, -140.0), Some(Basin::EasternNorthPacific));
+        assert_eq!(basin(91.0, 0.0), None);
+    }
+
+    #[test]
+    fn id() {
+        let id = "al092023".parse::<Id>().unwrap();
+        assert_eq!(id, "AL0923".parse().unwrap());
+        assert_eq!(id.to_string(), "AL092023");
+        assert_eq!("ep1050".parse::<Id>().unwrap().year(), 1950);
+        assert!(!id.is_invest());
+        assert!("AL952023".parse::<Id>().unwrap().is_invest());
+        for invalid in ["AL09202", "XX092023", "AL0920234", "AL-92023", "A"] {
+            assert!(invalid.parse::<Id>().is_err(), "{}", invalid);
+        }
+
+        let mut ids = ["AL102023", "EP012024", "AL092023", "WP092023", "AL011851"]
+            .map(|s| s.parse::<Id>().unwrap());
+        ids.sort();
+        assert_eq!(
+            ids.map(|id| id.to_string()),
+            ["AL011851", "AL092023", "WP092023", "AL102023", "EP012024"]
+        );
+
+        let bay = Id::new(Basin::NorthIndian, 2, 2024).with_sub_basin(Some(SubBasin::BayOfBengal));
+        assert_eq!(bay.designator(), "02B");
+        assert_eq!(bay, "IO022024".parse().unwrap());
+        let set = [bay, "io022024".parse().unwrap()]
+            .into_iter()
+            .collect::<HashSet<_>>();
+        assert_eq!(set.len(), 1);
+    }
+
+    const B_DECK: &str = "\
+AL, 10, 2023082606,   , BEST,   0, 195N,  862W,  25, 1006, DB,   0,    ,    0,    0,    0,    0, 1009,  150,  40,   0,   0,   L,   0,    ,   0,   0,     INVEST, S,
+AL, 10, 2023082618,   , BEST,   0, 205N,  867W,  30, 1004, TD,   0,    ,    0,    0,    0,    0, 1008,  180,  40,   0,   0,   L,   0,    ,   0,   0,        TEN, S,
+AL, 10, 2023083000,   , BEST,   0, 288N,  838W, 110,  942, HU,  34, NEQ,  130,  130,   70,   90, 1010,  240,  15, 135,  30,   L,   0,    , 355,  14,     IDALIA, D,
+AL, 10, 2023083000,   , BEST,   0, 288N,  838W, 110,  942, HU,  50, NEQ,   60,   60,   40,   50, 1010,  240,  15, 135,  30,   L,   0,    , 355,  14,     IDALIA, D,
+AL, 10, 2023083000,   , BEST,   0, 288N,  838W, 110,  942, HU,  64, NEQ,   35,   35,   25,   30, 1010,  240,  15, 135,  30,   L,   0,    , 355,  14,     IDALIA, D,
+AL, 10, 2023083011, 45, BEST,   0, 299N,  836W, 105,  949, HU,  34, AAA,  120,    0,    0,    0, 1010,  240,  15, 125,   0,   L,   0,    ,  15,  16,     IDALIA, D,
+";
+
+    #[test]
+    fn best_track() {
+        let track = BestTrack::read(B_DECK.as_bytes()).unwrap();
+        assert_eq!(track.id().to_string(), "AL102023");
+        assert_eq!(track.name(), Some("IDALIA"));
+        assert_eq!(track.fixes().len(), 4);
+
+        let fix = &track.fixes()[2];
+        assert_eq!(fix.max_wind(), 110);
+        assert_eq!(fix.min_pressure(), Some(942));
+        assert_eq!(fix.outer_pressure(), Some(1010));
+        assert_eq!(fix.outer_radius(), Some(240));
+        assert_eq!(fix.radius_of_max_wind(), Some(15));
+        assert_eq!(fix.eye_diameter(), Some(30));
+        let radius = |kts, f: fn(&crate::hurdat2::WindRadii) -> _| {
+            fix.wind_radii(kts)
+                .and_then(f)
+                .map(|d: crate::geo::Distance| d.in_nautical_miles().round())
+        };
+        assert_eq!(radius(34, |r| r.ne()), Some(130.0));
+        assert_eq!(radius(50, |r| r.sw()), Some(40.0));
+        assert_eq!(radius(64, |r| r.nw()), Some(30.0));
+
+        let landfall = &track.fixes()[3];
+        assert_eq!(landfall.time().format("%d %H%M").to_string(), "30 1145");
+        assert_eq!(landfall.location().lat(), 29.9);
+        assert_eq!(landfall.location().lng(), -83.6);
+        assert_eq!(landfall.eye_diameter(), None);
+
+        let storm = track.to_storm();
+        assert_eq!(storm.name(), Some("IDALIA"));
+        let statuses = storm.track().iter().map(|e| e.status()).collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                Status::Disturbance,
+                Status::TropicalDepression,
+                Status::Hurricane,
+                Status::Hurricane
+            ]
+        );
+        assert_eq!(
+            storm.track()[3]
+                .wind_radii(34)
+                .and_then(|r| r.sw())
+                .map(|d| d.in_nautical_miles().round()),
+            Some(120.0)
+        );
+    }
+}
@@ -0,0 +1,307 @@
+use std::path::{Path, PathBuf};
+
+use sha2::{Digest, Sha256};
+use tokio::{
+    fs,
+    io::{self, AsyncReadExt},
+};
+use tokio_stream::StreamExt;
+
+use crate::{
+    hurdat2::{Diagnostic, Hurdat2Stream, ParseMode, Storm},
+    ibtracs, DataDir, Error,
+};
+
+// MAGIC and VERSION begin every cache file. VERSION must be bumped whenever
+// the binary encoding of storms or diagnostics changes so that stale caches
+// are ignored.
+const MAGIC: &[u8; 4] = b"C5ST";
+const VERSION: u32 = 3;
+
+// load returns the storms in the HURDAT2 file at path, along with the
+// diagnostics produced by parsing it, from the binary cache in dir if there
+// is one for the file's contents. Otherwise the file is parsed leniently and
+// the repaired storms are cached with their diagnostics, so a file with
+// glitches is still only parsed once. In strict mode the first diagnostic,
+// cached or not, is returned as an error instead.
+pub async fn load<P: AsRef<Path>>(
+    dir: &DataDir<'_>,
+    path: P,
+    mode: ParseMode,
+) -> Result<(Vec<Storm>, Vec<Diagnostic>), Error> {
+    let path = path.as_ref();
+    let hash = source_hash(dir, path).await?;
+    let (storms, diagnostics) = match read(dir, &hash, None).await? {
+        Some(cached) => cached,
+        None => {
+            let r = fs::File::open(path).await?;
+            let mut stream = Hurdat2Stream::from_reader(r, ParseMode::Lenient);
+            let mut storms = vec![];
+            while let Some(storm) = stream.next().await {
+                storms.push(storm?);
+            }
+            let diagnostics = stream.diagnostics().to_vec();
+            write_or_warn(dir, &hash, None, path, &storms, &diagnostics).await;
+            (storms, diagnostics)
+        }
+    };
+    match (mode, diagnostics.first()) {
+        (ParseMode::Strict, Some(diagnostic)) => Err(Error::Parse(diagnostic.error().clone())),
+        _ => Ok((storms, diagnostics)),
+    }
+}
+
+// load_ibtracs returns the storms in the IBTrACS file at path, as given by
+// agency. Like load, it reads them from the cache in dir if it can and
+// caches them otherwise, keeping a separate cache for each agency.
+pub async fn load_ibtracs<P: AsRef<Path>>(
+    dir: &DataDir<'_>,
+    path: P,
+    agency: ibtracs::Agency,
+) -> Result<Vec<Storm>, Error> {
+    let path = path.as_ref();
+    let hash = source_hash(dir, path).await?;
+    let variant = Some(agency.to_str());
+    if let Some((storms, _)) = read(dir, &hash, variant).await? {
+        return Ok(storms);
+    }
+
+    let r = std::io::BufReader::new(fs::File::open(path).await?.into_std().await);
+    let options = ibtracs::Options::default().with_agency(agency);
+    let storms = ibtracs::read(r, &options)?
+        .into_iter()
+        .map(ibtracs::Track::into_storm)
+        .collect::<Vec<_>>();
+    write_or_warn(dir, &hash, variant, path, &storms, &[]).await;
+    Ok(storms)
+}
+
+// write_or_warn caches storms, only warning if it can't. The cache just saves
+// parsing next time, so failing to write it, say because the data dir is
+// read-only, doesn't stop the storms being used.
+async fn write_or_warn(
+    dir: &DataDir<'_>,
+    hash: &str,
+    variant: Option<&str>,
+    path: &Path,
+    storms: &[Storm],
+    diagnostics: &[Diagnostic],
+) {
+    if let Err(e) = write(dir, hash, variant, storms, diagnostics).await {
+        eprintln!("unable to cache storms from {}: {}", path.display(), e);
+    }
+}
+
+// read returns the cached storms, and the diagnostics produced when they were
+// parsed, for the source with the given hash. Sources that can be read in more
+// than one way, such as IBTrACS with each agency, name the variant that was
+// read. It returns None if there is no cache or it was written by another
+// version.
+pub async fn read(
+    dir: &DataDir<'_>,
+    hash: &str,
+    variant: Option<&str>,
+) -> io::Result<Option<(Vec<Storm>, Vec<Diagnostic>)>> {
+    let contents = match fs::read(cache_path(dir, hash, variant)).await {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+    match decode(&contents, hash) {
+        Ok(cached) => Ok(cached),
+        // A truncated or corrupt cache is as good as a missing one; it will
+        // be replaced the next time the source is parsed.
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => Ok(None),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+// write caches storms parsed from the source with the given hash, along with
+// the diagnostics produced parsing them.
+pub async fn write(
+    dir: &DataDir<'_>,
+    hash: &str,
+    variant: Option<&str>,
+    storms: &[Storm],
+    diagnostics: &[Diagnostic],
+) -> io::Result<()> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(MAGIC);
+    buf.extend_from_slice(&VERSION.to_le_bytes());
+    buf.extend_from_slice(&hex::decode(hash).map_err(invalid_data)?);
+    buf.extend_from_slice(&(storms.len() as u32).to_le_bytes());
+    for storm in storms {
+        storm.write_binary(&mut buf)?;
+    }
+    buf.extend_from_slice(&(diagnostics.len() as u32).to_le_bytes());
+    for diagnostic in diagnostics {
+        diagnostic.write_binary(&mut buf)?;
+    }
+
+    // The cache is written to a temporary file and renamed into place so that
+    // concurrent readers never see a partial file.
+    let path = cache_path(dir, hash, variant);
+    let tmp = path.with_extension("storms.tmp");
+    fs::write(&tmp, &buf).await?;
+    fs::rename(&tmp, &path).await
+}
+
+// source_hash returns the sha256 of the file at path. Files fetched into
+// dir are already named by their hash, so only other files need to be read.
+pub async fn source_hash(dir: &DataDir<'_>, path: &Path) -> io::Result<String> {
+    let path = fs::canonicalize(path).await?;
+    let in_dir = match fs::canonicalize(dir.path()).await {
+        Ok(dir) => path.parent() == Some(dir.as_path()),
+        Err(_) => false,
+    };
+    if let Some(name) = path.file_name().and_then(|n| n.to_str()) {
+        if in_dir && name.len() == 64 && name.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Ok(name.to_ascii_lowercase());
+        }
+    }
+    let mut file = fs::File::open(&path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0; 1 << 16];
+    loop {
+        match file.read(&mut buf).await? {
+            0 => break,
+            n => hasher.update(&buf[..n]),
+        }
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn cache_path(dir: &DataDir<'_>, hash: &str, variant: Option<&str>) -> PathBuf {
+    match variant {
+        Some(variant) => dir.path().join(format!("{}-{}.storms", hash, variant)),
+        None => dir.path().join(format!("{}.storms", hash)),
+    }
+}
+
+fn decode(mut r: &[u8], hash: &str) -> io::Result<Option<(Vec<Storm>, Vec<Diagnostic>)>> {
+    let mut header = [0; 8];
+    std::io::Read::read_exact(&mut r, &mut header)?;
+    if &header[..4] != MAGIC || header[4..] != VERSION.to_le_bytes() {
+        return Ok(None);
+    }
+    let mut source = [0; 32];
+    std::io::Read::read_exact(&mut r, &mut source)?;
+    if !hex::encode(source).eq_ignore_ascii_case(hash) {
+        return Ok(None);
+    }
+    let storms = (0..read_count(&mut r)?)
+        .map(|_| Storm::read_binary(&mut r))
+        .collect::<io::Result<Vec<_>>>()?;
+    let diagnostics = (0..read_count(&mut r)?)
+        .map(|_| Diagnostic::read_binary(&mut r))
+        .collect::<io::Result<Vec<_>>>()?;
+    Ok(Some((storms, diagnostics)))
+}
+
+fn read_count(r: &mut &[u8]) -> io::Result<u32> {
+    let mut count = [0; 4];
+    std::io::Read::read_exact(r, &mut count)?;
+    Ok(u32::from_le_bytes(count))
+}
+
+fn invalid_data<E>(e: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        hurdat2::{Hurdat2Writer, ParseMode},
+        DataDir,
+    };
+
+    const SAMPLE: &str = "\
+AL122005,            KATRINA,      2,
+20050823, 1800,  , TD, 23.1N,  75.1W,  30, 1008, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20050829, 1110, L, HU, 29.3N,  89.6W, 110,  920,  200,  200,  150,  100,  110,  110,   80,   60,   90,   90,   45,   35,   20,
+";
+
+    #[tokio::test]
+    async fn round_trip() {
+        let tmp = std::env::temp_dir().join(format!("cat5-cache-{}", std::process::id()));
+        let client = reqwest::Client::new();
+        let dir = DataDir::create(&client, &tmp).await.unwrap();
+        let src = tmp.join("hurdat2.txt");
+        tokio::fs::write(&src, SAMPLE).await.unwrap();
+
+        let (parsed, diagnostics) = super::load(&dir, &src, ParseMode::Strict).await.unwrap();
+        assert!(diagnostics.is_empty());
+
+        let hash = super::source_hash(&dir, &src).await.unwrap();
+        let (cached, cached_diagnostics) = super::read(&dir, &hash, None).await.unwrap().unwrap();
+        assert!(cached_diagnostics.is_empty());
+        let to_hurdat2 = |storms: &[crate::hurdat2::Storm]| {
+            let mut w = Hurdat2Writer::new(Vec::new());
+            for storm in storms {
+                w.write(storm).unwrap();
+            }
+            String::from_utf8(w.into_inner()).unwrap()
+        };
+        assert_eq!(to_hurdat2(&cached), to_hurdat2(&parsed));
+        assert_eq!(to_hurdat2(&cached), SAMPLE);
+        assert!(super::read(&dir, &"0".repeat(64), None)
+            .await
+            .unwrap()
+            .is_none());
+        assert!(super::read(&dir, &hash, Some("tokyo"))
+            .await
+            .unwrap()
+            .is_none());
+
+        // A corrupt track count whose header still matches is treated as a
+        // missing cache rather than exhausting memory.
+        let path = super::cache_path(&dir, &hash, None);
+        let mut contents = tokio::fs::read(&path).await.unwrap();
+        let count = 4 + 4 + 32 + 4 + (1 + "AL122005".len()) + (1 + "KATRINA".len());
+        assert_eq!(contents[count..count + 4], 2u32.to_le_bytes());
+        contents[count..count + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        tokio::fs::write(&path, contents).await.unwrap();
+        assert!(super::read(&dir, &hash, None).await.unwrap().is_none());
+
+        tokio::fs::remove_dir_all(&tmp).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn diagnostics() {
+        let tmp = std::env::temp_dir().join(format!("cat5-cache-diag-{}", std::process::id()));
+        let client = reqwest::Client::new();
+        let dir = DataDir::create(&client, &tmp).await.unwrap();
+        let src = tmp.join("hurdat2.txt");
+        tokio::fs::write(&src, SAMPLE.replace(" 920,", " 9x0,"))
+            .await
+            .unwrap();
+
+        let (parsed, diagnostics) = super::load(&dir, &src, ParseMode::Lenient).await.unwrap();
+        assert_eq!(diagnostics.len(), 1);
+
+        // The repaired storms are cached along with their diagnostics, so
+        // loading the file again returns both without parsing it.
+        let hash = super::source_hash(&dir, &src).await.unwrap();
+        let (cached, cached_diagnostics) = super::read(&dir, &hash, None).await.unwrap().unwrap();
+        assert_eq!(cached.len(), parsed.len());
+        assert_eq!(cached_diagnostics.len(), 1);
+        assert_eq!(
+            cached_diagnostics[0].to_string(),
+            diagnostics[0].to_string()
+        );
+        let (_, loaded) = super::load(&dir, &src, ParseMode::Lenient).await.unwrap();
+        assert_eq!(loaded[0].to_string(), diagnostics[0].to_string());
+
+        // Strict loads still fail on a cached file with diagnostics.
+        let err = super::load(&dir, &src, ParseMode::Strict)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, crate::Error::Parse(_)));
+
+        tokio::fs::remove_dir_all(&tmp).await.unwrap();
+    }
+}
@@ -0,0 +1,268 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+
+use crate::{atcf, hurdat2::Storm};
+
+// UNNAMED is the name that unnamed storms are indexed under, which is how
+// HURDAT2 writes them.
+const UNNAMED: &str = "UNNAMED";
+
+// StormCatalog owns a collection of storms and indexes them for lookup by id,
+// name and year, season, basin and the time they were active. Name lookups
+// ignore case.
+#[derive(Debug)]
+pub struct StormCatalog {
+    storms: Vec<Storm>,
+    by_id: HashMap<atcf::Id, usize>,
+    by_name: HashMap<(String, i32), Vec<usize>>,
+    by_year: BTreeMap<i32, Vec<usize>>,
+    by_basin: BTreeMap<atcf::Basin, Vec<usize>>,
+    active: IntervalIndex,
+}
+
+impl StormCatalog {
+    pub fn new(storms: Vec<Storm>) -> StormCatalog {
+        let mut by_id = HashMap::new();
+        let mut by_name: HashMap<_, Vec<_>> = HashMap::new();
+        let mut by_year: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        let mut by_basin: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (i, storm) in storms.iter().enumerate() {
+            let id = storm.id();
+            by_id.insert(id.clone(), i);
+            by_name
+                .entry((name_key(storm.name()), id.year()))
+                .or_default()
+                .push(i);
+            by_year.entry(id.year()).or_default().push(i);
+            by_basin.entry(id.basin()).or_default().push(i);
+        }
+        let active = IntervalIndex::new(&storms);
+        StormCatalog {
+            storms,
+            by_id,
+            by_name,
+            by_year,
+            by_basin,
+            active,
+        }
+    }
+
+    pub fn storms(&self) -> &[Storm] {
+        &self.storms
+    }
+
+    pub fn len(&self) -> usize {
+        self.storms.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.storms.is_empty()
+    }
+
+    pub fn get(&self, id: &atcf::Id) -> Option<&Storm> {
+        self.by_id.get(id).map(|&i| &self.storms[i])
+    }
+
+    // by_name returns the storms with the given name in the year's season.
+    // There may be several when the name is UNNAMED, which matches storms
+    // that were never named.
+    pub fn by_name(&self, name: &str, year: i32) -> Vec<&Storm> {
+        self.resolve(self.by_name.get(&(name_key(Some(name)), year)))
+    }
+
+    pub fn by_year(&self, year: i32) -> Vec<&Storm> {
+        self.resolve(self.by_year.get(&year))
+    }
+
+    pub fn by_basin(&self, basin: atcf::Basin) -> Vec<&Storm> {
+        self.resolve(self.by_basin.get(&basin))
+    }
+
+    // years returns the seasons that have storms, in order.
+    pub fn years(&self) -> impl Iterator<Item = i32> + '_ {
+        self.by_year.keys().copied()
+    }
+
+    // active_between returns the storms whose tracks overlap the inclusive
+    // range [start, end], ordered by the time of their first entry.
+    pub fn active_between(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Vec<&Storm> {
+        let mut found = vec![];
+        self.active.query(start, end, &mut found);
+        found.into_iter().map(|i| &self.storms[i]).collect()
+    }
+
+    fn resolve(&self, indices: Option<&Vec<usize>>) -> Vec<&Storm> {
+        indices
+            .map(|ix| ix.iter().map(|&i| &self.storms[i]).collect())
+            .unwrap_or_default()
+    }
+}
+
+impl From<Vec<Storm>> for StormCatalog {
+    fn from(storms: Vec<Storm>) -> Self {
+        StormCatalog::new(storms)
+    }
+}
+
+fn name_key(name: Option<&str>) -> String {
+    name.unwrap_or(UNNAMED).trim().to_uppercase()
+}
+
+// IntervalIndex finds the storms active during a time range. The intervals
+// are kept sorted by start and treated as an implicit balanced tree, where
+// the middle of each range is the root of that range's subtree and records
+// the latest end time of any interval in it.
+#[derive(Debug)]
+struct IntervalIndex {
+    intervals: Vec<Interval>,
+    max_end: Vec<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Interval {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    storm: usize,
+}
+
+impl IntervalIndex {
+    fn new(storms: &[Storm]) -> IntervalIndex {
+        let mut intervals = storms
+            .iter()
+            .enumerate()
+            .filter_map(|(i, s)| match (s.track().first(), s.track().last()) {
+                (Some(first), Some(last)) => Some(Interval {
+                    start: first.time(),
+                    end: last.time(),
+                    storm: i,
+                }),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        intervals.sort_by_key(|iv| (iv.start, iv.storm));
+        let mut max_end = intervals.iter().map(|iv| iv.end).collect::<Vec<_>>();
+        Self::fill_max_end(&intervals, &mut max_end, 0, intervals.len());
+        IntervalIndex { intervals, max_end }
+    }
+
+    fn fill_max_end(
+        intervals: &[Interval],
+        max_end: &mut [DateTime<Utc>],
+        lo: usize,
+        hi: usize,
+    ) -> Option<DateTime<Utc>> {
+        if lo >= hi {
+            return None;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let left = Self::fill_max_end(intervals, max_end, lo, mid);
+        let right = Self::fill_max_end(intervals, max_end, mid + 1, hi);
+        let end = [left, right]
+            .into_iter()
+            .flatten()
+            .fold(intervals[mid].end, DateTime::max);
+        max_end[mid] = end;
+        Some(end)
+    }
+
+    fn query(&self, start: DateTime<Utc>, end: DateTime<Utc>, found: &mut Vec<usize>) {
+        self.query_range(start, end, 0, self.intervals.len(), found);
+    }
+
+    fn query_range(
+        &self,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        lo: usize,
+        hi: usize,
+        found: &mut Vec<usize>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        if self.max_end[mid] < start {
+            return;
+        }
+        self.query_range(start, end, lo, mid, found);
+        let iv = &self.intervals[mid];
+        if iv.start > end {
+            return;
+        }
+        if iv.end >= start {
+            found.push(iv.storm);
+        }
+        self.query_range(start, end, mid + 1, hi, found);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+
+    use super::StormCatalog;
+    use crate::{
+        atcf::Basin,
+        hurdat2::{Hurdat2Reader, ParseMode},
+    };
+
+    const SAMPLE: &str = "\
+AL011851,            UNNAMED,      2,
+18510625, 0000,  , HU, 28.0N,  94.8W,  80, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+18510626, 0000,  , HU, 28.5N,  95.5W,  70, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+AL021851,            UNNAMED,      1,
+18510705, 1200,  , HU, 22.2N,  97.6W,  80, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+AL122005,            KATRINA,      2,
+20050823, 1800,  , TD, 23.1N,  75.1W,  30, 1008, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20050830, 0600,  , LO, 38.6N,  85.3W,  30,  996, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+EP122005,             JOVA,      2,
+20050917, 0000,  , TD, 12.5N, 125.4W,  30, 1007, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20050925, 0000,  , LO, 18.3N, 158.2W,  25, 1010, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+";
+
+    #[test]
+    fn lookups() {
+        let storms = Hurdat2Reader::new(SAMPLE.as_bytes(), ParseMode::Strict)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let catalog = StormCatalog::new(storms);
+        let ids = |storms: Vec<&crate::hurdat2::Storm>| {
+            storms
+                .iter()
+                .map(|s| s.id().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let katrina = catalog.get(&"AL122005".parse().unwrap()).unwrap();
+        assert_eq!(katrina.name(), Some("KATRINA"));
+        assert!(catalog.get(&"AL132005".parse().unwrap()).is_none());
+
+        assert_eq!(ids(catalog.by_name("katrina", 2005)), vec!["AL122005"]);
+        assert!(catalog.by_name("katrina", 2006).is_empty());
+        assert_eq!(
+            ids(catalog.by_name("Unnamed", 1851)),
+            vec!["AL011851", "AL021851"]
+        );
+        assert_eq!(ids(catalog.by_year(2005)), vec!["AL122005", "EP122005"]);
+        assert_eq!(
+            ids(catalog.by_basin(Basin::EasternNorthPacific)),
+            vec!["EP122005"]
+        );
+        assert_eq!(catalog.years().collect::<Vec<_>>(), vec![1851, 2005]);
+
+        let t = |y, m, d| Utc.with_ymd_and_hms(y, m, d, 0, 0, 0).unwrap();
+        assert_eq!(
+            ids(catalog.active_between(t(1851, 6, 26), t(1851, 7, 10))),
+            vec!["AL011851", "AL021851"]
+        );
+        assert_eq!(
+            ids(catalog.active_between(t(2005, 8, 1), t(2005, 12, 31))),
+            vec!["AL122005", "EP122005"]
+        );
+        assert_eq!(
+            ids(catalog.active_between(t(2005, 9, 1), t(2005, 9, 16))),
+            Vec::<String>::new()
+        );
+    }
+}
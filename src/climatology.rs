@@ -0,0 +1,248 @@
+use std::collections::HashSet;
+
+use chrono::{Datelike, NaiveDate};
+use serde::Serialize;
+
+use crate::hurdat2::{Status, Storm, TrackEntry};
+
+// DAYS is the number of days in the day-of-year index. Days are indexed as in
+// a leap year so that February 29th has its own slot and every other date has
+// the same index in every year.
+pub const DAYS: usize = 366;
+
+// Climatology is the average daily tropical cyclone activity for each day of
+// the year over a range of years.
+#[derive(Debug, Clone, Serialize)]
+pub struct Climatology {
+    start_year: i32,
+    end_year: i32,
+    smoothing: usize,
+    days: Vec<Day>,
+}
+
+// Day is the average activity for a single day of the year. A storm is active
+// on a day if any of its track entries on that day have tropical or
+// subtropical status and it is counted as a hurricane or major hurricane if
+// any of those entries were at that strength.
+#[derive(Debug, Clone, Serialize)]
+pub struct Day {
+    doy: usize,
+    month: u32,
+    day: u32,
+    storms: f64,
+    hurricanes: f64,
+    major_hurricanes: f64,
+}
+
+impl Day {
+    // doy is the index of the day, starting at 0 for January 1st.
+    pub fn doy(&self) -> usize {
+        self.doy
+    }
+
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    pub fn storms(&self) -> f64 {
+        self.storms
+    }
+
+    pub fn hurricanes(&self) -> f64 {
+        self.hurricanes
+    }
+
+    pub fn major_hurricanes(&self) -> f64 {
+        self.major_hurricanes
+    }
+}
+
+impl Climatology {
+    // from_storms computes the climatology of the given storms over the years
+    // start_year through end_year inclusive. Activity outside of those years
+    // is ignored.
+    pub fn from_storms<'a, I>(storms: I, start_year: i32, end_year: i32) -> Climatology
+    where
+        I: IntoIterator<Item = &'a Storm>,
+    {
+        let mut counts = [[0usize; 3]; DAYS];
+        for storm in storms {
+            let mut seen = HashSet::new();
+            for e in storm.track() {
+                let date = e.time().date_naive();
+                if date.year() < start_year || date.year() > end_year || !is_active(e) {
+                    continue;
+                }
+                let doy = doy_of(date);
+                let levels = [
+                    true,
+                    is_hurricane(e),
+                    is_hurricane(e) && e.category().is_major(),
+                ];
+                for (level, _) in levels.iter().enumerate().filter(|(_, &l)| l) {
+                    if seen.insert((date, level)) {
+                        counts[doy][level] += 1;
+                    }
+                }
+            }
+        }
+
+        let years = (end_year - start_year + 1).max(0) as f64;
+        let leap_years = (start_year..=end_year)
+            .filter(|&y| NaiveDate::from_ymd_opt(y, 2, 29).is_some())
+            .count() as f64;
+        let days = counts
+            .iter()
+            .enumerate()
+            .map(|(doy, c)| {
+                let date = date_of(doy);
+                let n = if date.month() == 2 && date.day() == 29 {
+                    leap_years
+                } else {
+                    years
+                };
+                let avg = |v: usize| if n > 0.0 { v as f64 / n } else { 0.0 };
+                Day {
+                    doy,
+                    month: date.month(),
+                    day: date.day(),
+                    storms: avg(c[0]),
+                    hurricanes: avg(c[1]),
+                    major_hurricanes: avg(c[2]),
+                }
+            })
+            .collect();
+
+        Climatology {
+            start_year,
+            end_year,
+            smoothing: 1,
+            days,
+        }
+    }
+
+    // smoothed returns the climatology with each day replaced by the mean of
+    // the window days centered on it. The window wraps around the end of the
+    // year and is rounded up to an odd number of days, but is never wider
+    // than the year.
+    pub fn smoothed(&self, window: usize) -> Climatology {
+        let half = (window / 2).min((DAYS - 1) / 2);
+        let n = (2 * half + 1) as f64;
+        let mean = |doy: usize, f: fn(&Day) -> f64| {
+            (0..=2 * half)
+                .map(|k| {
+                    let i = (doy + k) as isize - half as isize;
+                    f(&self.days[i.rem_euclid(DAYS as isize) as usize])
+                })
+                .sum::<f64>()
+                / n
+        };
+        Climatology {
+            start_year: self.start_year,
+            end_year: self.end_year,
+            smoothing: 2 * half + 1,
+            days: self
+                .days
+                .iter()
+                .map(|d| Day {
+                    storms: mean(d.doy, Day::storms),
+                    hurricanes: mean(d.doy, Day::hurricanes),
+                    major_hurricanes: mean(d.doy, Day::major_hurricanes),
+                    ..d.clone()
+                })
+                .collect(),
+        }
+    }
+
+    pub fn start_year(&self) -> i32 {
+        self.start_year
+    }
+
+    pub fn end_year(&self) -> i32 {
+        self.end_year
+    }
+
+    // smoothing is the width, in days, of the window the climatology was
+    // smoothed with. A width of 1 means no smoothing.
+    pub fn smoothing(&self) -> usize {
+        self.smoothing
+    }
+
+    pub fn days(&self) -> &[Day] {
+        &self.days
+    }
+}
+
+fn is_active(e: &TrackEntry) -> bool {
+    matches!(
+        e.status(),
+        Status::TropicalDepression
+            | Status::TropicalStorm
+            | Status::Hurricane
+            | Status::SubtropicalDepression
+            | Status::SubtropicalStorm
+    )
+}
+
+fn is_hurricane(e: &TrackEntry) -> bool {
+    e.status() == Status::Hurricane && e.category().is_hurricane()
+}
+
+// doy_of returns the index of the date's month and day within a leap year.
+fn doy_of(date: NaiveDate) -> usize {
+    NaiveDate::from_ymd_opt(2000, date.month(), date.day())
+        .unwrap()
+        .ordinal0() as usize
+}
+
+fn date_of(doy: usize) -> NaiveDate {
+    NaiveDate::from_yo_opt(2000, doy as u32 + 1).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Climatology, DAYS};
+    use crate::hurdat2::{Hurdat2Reader, ParseMode};
+
+    const SAMPLE: &str = "\
+AL012020,             ARTHUR,      3,
+20200229, 1800,  , LO, 28.0N,  78.7W,  25, 1008, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200301, 0000,  , TS, 28.3N,  78.0W,  40, 1008, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200301, 0600,  , HU, 29.0N,  77.3W, 100,  960, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+AL012021,               ANNA,      2,
+20210301, 1800,  , TS, 32.0N,  79.0W,  40,  990, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20210302, 0000,  , TS, 33.0N,  80.0W,  50,  980, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+";
+
+    #[test]
+    fn climatology() {
+        let storms = Hurdat2Reader::new(SAMPLE.as_bytes(), ParseMode::Strict)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let c = Climatology::from_storms(&storms, 2020, 2021);
+        let days = c.days();
+        assert_eq!(days.len(), 366);
+        assert_eq!((days[59].month(), days[59].day()), (2, 29));
+        assert_eq!(days[59].storms(), 0.0);
+        assert_eq!((days[60].month(), days[60].day()), (3, 1));
+        assert_eq!(days[60].storms(), 1.0);
+        assert_eq!(days[60].hurricanes(), 0.5);
+        assert_eq!(days[60].major_hurricanes(), 0.5);
+        assert_eq!(days[61].storms(), 0.5);
+
+        let s = c.smoothed(3);
+        assert_eq!(s.smoothing(), 3);
+        assert_eq!(s.days()[60].storms(), 0.5);
+        assert_eq!(s.days()[62].storms(), 0.5 / 3.0);
+
+        // Windows wider than a year are narrowed to fit within it.
+        let total = |c: &Climatology| c.days().iter().map(|d| d.storms()).sum::<f64>();
+        assert_eq!(c.smoothed(366).smoothing(), DAYS - 1);
+        assert_eq!(c.smoothed(1000).smoothing(), DAYS - 1);
+        assert!((total(&c.smoothed(1000)) - total(&c)).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::{noaa, DataDir, Error};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[clap(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum Command {
+    #[clap(about = "list the fetched revisions of a dataset")]
+    History {
+        #[clap(help = "dataset name or the name of an object in the data dir")]
+        name: String,
+    },
+
+    #[clap(about = "point a dataset at an earlier revision")]
+    Pin {
+        #[clap(help = "dataset name or the name of an object in the data dir")]
+        name: String,
+
+        #[clap(help = "hash, or unambiguous prefix of one, of the revision")]
+        hash: String,
+    },
+}
+
+pub async fn run(dir: &DataDir<'_>, args: &Args) -> Result<(), Error> {
+    match &args.command {
+        Command::History { name } => {
+            let object = dir.get_object(&object_name(name));
+            let current = object.current().await?;
+            for revision in object.history().await? {
+                let marker = if current.as_deref() == Some(revision.hash()) {
+                    '*'
+                } else {
+                    ' '
+                };
+                println!(
+                    "{} {} {} {}",
+                    marker,
+                    revision.hash(),
+                    revision.fetched_at().format("%Y-%m-%d %H:%M:%SZ"),
+                    revision.url()
+                );
+            }
+        }
+        Command::Pin { name, hash } => {
+            let object = dir.get_object(&object_name(name));
+            let revision = object.pin(hash).await?;
+            println!(
+                "{} now refers to {} (fetched {})",
+                name,
+                revision.hash(),
+                revision.fetched_at().format("%Y-%m-%d %H:%M:%SZ")
+            );
+        }
+    }
+    Ok(())
+}
+
+// object_name maps a dataset name to the name of its object in the data dir.
+// Any other name is taken to be an object name already.
+fn object_name(name: &str) -> String {
+    match name.parse::<noaa::Dataset>() {
+        Ok(dataset) => dataset.object_name(),
+        Err(_) => name.to_owned(),
+    }
+}
@@ -1,14 +1,13 @@
-use std::{
-    error::Error,
-    path::{Path, PathBuf},
-};
+use std::path::{Path, PathBuf};
 
+use chrono::{DateTime, Utc};
 use reqwest::{
     header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
     StatusCode,
 };
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use thiserror::Error;
 use tokio::{
     fs,
     io::{self, AsyncReadExt, AsyncWriteExt},
@@ -21,10 +20,7 @@ pub struct DataDir<'a> {
 }
 
 impl<'a> DataDir<'a> {
-    pub async fn create<P: AsRef<Path>>(
-        client: &'a reqwest::Client,
-        dir: P,
-    ) -> Result<Self, Box<dyn Error>> {
+    pub async fn create<P: AsRef<Path>>(client: &'a reqwest::Client, dir: P) -> io::Result<Self> {
         let dir = dir.as_ref();
         if !dir.exists() {
             fs::create_dir_all(dir).await?;
@@ -40,7 +36,7 @@ impl<'a> DataDir<'a> {
         &self.path
     }
 
-    pub fn get_object(&self, name: &str) -> Object {
+    pub fn get_object(&self, name: &str) -> Object<'_> {
         Object {
             dir: self,
             name: name.to_owned(),
@@ -59,7 +55,7 @@ impl<'a> Object<'a> {
         self.dir.path.join(&self.name)
     }
 
-    pub async fn fetch(self, url: &str, strategy: FetchStrategy) -> Result<Self, Box<dyn Error>> {
+    pub async fn fetch(self, url: &str, strategy: FetchStrategy) -> Result<Self, FetchError> {
         let path = self.path();
         match strategy {
             FetchStrategy::Always => {
@@ -80,17 +76,138 @@ impl<'a> Object<'a> {
         }
     }
 
-    pub async fn open(&self) -> Result<fs::File, Box<dyn Error>> {
+    pub async fn open(&self) -> io::Result<fs::File> {
         let path = self.path();
-        Ok(fs::File::open(&path).await?)
+        fs::File::open(&path).await
     }
 
-    pub async fn create(&self) -> Result<fs::File, Box<dyn Error>> {
+    pub async fn create(&self) -> io::Result<fs::File> {
         let path = self.path();
-        Ok(fs::File::create(&path).await?)
+        fs::File::create(&path).await
+    }
+
+    // history returns every revision of the object that has been fetched,
+    // oldest first.
+    pub async fn history(&self) -> io::Result<Vec<Revision>> {
+        let contents = match fs::read(self.history_path()).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Ok(self.untracked_revision("").await?.into_iter().collect())
+            }
+            Err(e) => return Err(e),
+        };
+        serde_json::Deserializer::from_slice(&contents)
+            .into_iter::<Revision>()
+            .map(|r| r.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)))
+            .collect()
+    }
+
+    // current returns the hash of the revision the object currently refers
+    // to, if it exists.
+    pub async fn current(&self) -> io::Result<Option<String>> {
+        match fs::read_link(self.path()).await {
+            Ok(target) => Ok(target.file_name().map(|n| n.to_string_lossy().into_owned())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    // revision_at returns the revision that was current at time t, which is
+    // the last one fetched at or before t.
+    pub async fn revision_at(&self, t: DateTime<Utc>) -> io::Result<Option<Revision>> {
+        Ok(self
+            .history()
+            .await?
+            .into_iter()
+            .filter(|r| r.fetched_at <= t)
+            .max_by_key(|r| r.fetched_at))
+    }
+
+    // pin points the object back at an earlier revision. The hash may be
+    // abbreviated as long as it is unambiguous. A later fetch will move the
+    // object to a newer revision if there is one.
+    pub async fn pin(&self, hash: &str) -> io::Result<Revision> {
+        let mut found = self
+            .history()
+            .await?
+            .into_iter()
+            .filter(|r| r.hash.starts_with(hash))
+            .collect::<Vec<_>>();
+        found.dedup_by(|a, b| a.hash == b.hash);
+        let revision = match found.len() {
+            0 => {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no revision {} of {}", hash, self.name),
+                ))
+            }
+            1 => found.remove(0),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("revision {} of {} is ambiguous", hash, self.name),
+                ))
+            }
+        };
+        symlink(&revision.hash, self.path()).await?;
+        Ok(revision)
+    }
+
+    // revision_path returns the path of the file holding the contents of
+    // revision.
+    pub fn revision_path(&self, revision: &Revision) -> PathBuf {
+        self.dir.path.join(&revision.hash)
+    }
+
+    // untracked_revision returns the revision the object refers to if it was
+    // fetched before histories were kept and so has no history file. The
+    // revision is dated by when its file was written.
+    async fn untracked_revision(&self, url: &str) -> io::Result<Option<Revision>> {
+        if fs::try_exists(self.history_path()).await? {
+            return Ok(None);
+        }
+        let hash = match self.current().await? {
+            Some(hash) => hash,
+            None => return Ok(None),
+        };
+        let fetched_at = fs::metadata(self.dir.path.join(&hash))
+            .await?
+            .modified()?
+            .into();
+        let md = Metadata::from_link(self.path())
+            .await
+            .unwrap_or_else(|_| Metadata::empty());
+        Ok(Some(Revision {
+            hash,
+            url: url.to_owned(),
+            fetched_at,
+            last_modified: md.last_modified,
+            etag: md.etag,
+        }))
+    }
+
+    fn history_path(&self) -> PathBuf {
+        self.dir.path.join(format!("{}.history", self.name))
     }
 
-    async fn download(&self, url: &str, md: &Metadata) -> Result<(), Box<dyn Error>> {
+    async fn append_history(&self, revision: &Revision) -> io::Result<()> {
+        let mut line = serde_json::to_vec(revision)?;
+        line.push(b'\n');
+        fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.history_path())
+            .await?
+            .write_all(&line)
+            .await
+    }
+
+    async fn download(&self, url: &str, md: &Metadata) -> Result<(), FetchError> {
+        let request_error = |source| FetchError::Request {
+            url: url.to_owned(),
+            source,
+        };
+
         let mut req = self.dir.client.get(url);
         if let Some(last_modified) = &md.last_modified {
             req = req.header(IF_MODIFIED_SINCE, last_modified);
@@ -98,12 +215,20 @@ impl<'a> Object<'a> {
             req = req.header(IF_NONE_MATCH, etag);
         }
 
-        let res = req.send().await?;
+        let res = req.send().await.map_err(request_error)?;
+
+        // An object fetched before histories were kept starts its history
+        // with the revision it already has, so that it can still be pinned
+        // and read --as-of once a newer one arrives.
+        if let Some(revision) = self.untracked_revision(url).await? {
+            self.append_history(&revision).await?;
+        }
+
         match res.status() {
             StatusCode::OK => {
-                let md = Metadata::from_headers(res.headers())?;
+                let md = Metadata::from_headers(url, res.headers())?;
 
-                let content = res.bytes().await?;
+                let content = res.bytes().await.map_err(request_error)?;
 
                 let hash = hex::encode(Sha256::digest(content.as_ref()));
 
@@ -119,14 +244,83 @@ impl<'a> Object<'a> {
 
                 let link = self.dir.path.join(&self.name);
                 symlink(&hash, &link).await?;
+
+                self.append_history(&Revision {
+                    hash,
+                    url: url.to_owned(),
+                    fetched_at: Utc::now(),
+                    last_modified: md.last_modified,
+                    etag: md.etag,
+                })
+                .await?;
                 Ok(())
             }
             StatusCode::NOT_MODIFIED => Ok(()),
-            s => Err(format!("unexpected status code: {}", s).into()),
+            status => Err(FetchError::Status {
+                url: url.to_owned(),
+                status,
+            }),
         }
     }
 }
 
+// Revision is an entry in an object's history, recorded each time a new
+// version of it is fetched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revision {
+    hash: String,
+    url: String,
+    fetched_at: DateTime<Utc>,
+    last_modified: Option<String>,
+    etag: Option<String>,
+}
+
+impl Revision {
+    // hash is the sha256 of the revision's contents, which is also the name
+    // of the file it is stored in.
+    pub fn hash(&self) -> &str {
+        &self.hash
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    pub fn fetched_at(&self) -> DateTime<Utc> {
+        self.fetched_at
+    }
+
+    pub fn last_modified(&self) -> Option<&str> {
+        self.last_modified.as_deref()
+    }
+
+    pub fn etag(&self) -> Option<&str> {
+        self.etag.as_deref()
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum FetchError {
+    #[error("request for {url} failed: {source}")]
+    Request {
+        url: String,
+        #[source]
+        source: reqwest::Error,
+    },
+
+    #[error("unexpected status code {status} for {url}")]
+    Status { url: String, status: StatusCode },
+
+    #[error("invalid {header} header for {url}")]
+    Header { url: String, header: &'static str },
+
+    #[error("invalid metadata: {0}")]
+    Metadata(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum FetchStrategy {
     IfMissing,
@@ -148,14 +342,23 @@ impl Metadata {
         }
     }
 
-    fn from_headers(headers: &reqwest::header::HeaderMap) -> Result<Self, Box<dyn Error>> {
+    fn from_headers(url: &str, headers: &reqwest::header::HeaderMap) -> Result<Self, FetchError> {
+        let header_error = |header| FetchError::Header {
+            url: url.to_owned(),
+            header,
+        };
+
         let last_modified = match headers.get(LAST_MODIFIED) {
-            Some(v) => Some(v.to_str()?.to_owned()),
+            Some(v) => Some(
+                v.to_str()
+                    .map_err(|_| header_error("last-modified"))?
+                    .to_owned(),
+            ),
             None => None,
         };
 
         let etag = match headers.get(ETAG) {
-            Some(v) => Some(v.to_str()?.to_owned()),
+            Some(v) => Some(v.to_str().map_err(|_| header_error("etag"))?.to_owned()),
             None => None,
         };
 
@@ -165,19 +368,18 @@ impl Metadata {
         })
     }
 
-    async fn from_link<P>(link: P) -> Result<Self, Box<dyn Error>>
+    async fn from_link<P>(link: P) -> Result<Self, FetchError>
     where
         P: AsRef<Path>,
     {
-        let dir = link
-            .as_ref()
-            .parent()
-            .ok_or("link has not parent directory")?;
+        let dir = link.as_ref().parent().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "link has no parent directory")
+        })?;
         let path = fs::read_link(&link).await?;
         Self::from_reader(&mut fs::File::open(dir.join(path).with_extension("meta")).await?).await
     }
 
-    async fn from_reader<R>(r: &mut R) -> Result<Self, Box<dyn Error>>
+    async fn from_reader<R>(r: &mut R) -> Result<Self, FetchError>
     where
         R: io::AsyncRead + Unpin,
     {
@@ -186,7 +388,7 @@ impl Metadata {
         Ok(serde_json::from_slice(&contents)?)
     }
 
-    async fn to_writer<W>(&self, w: &mut W) -> Result<(), Box<dyn Error>>
+    async fn to_writer<W>(&self, w: &mut W) -> Result<(), FetchError>
     where
         W: io::AsyncWrite + Unpin,
     {
@@ -201,3 +403,71 @@ async fn symlink(src: impl AsRef<Path>, dst: impl AsRef<Path>) -> io::Result<()>
     fs::symlink(src, &tmp).await?;
     fs::rename(&tmp, dst).await
 }
+
+#[cfg(test)]
+mod test {
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use tokio::fs;
+
+    use super::{symlink, DataDir, Revision};
+
+    fn revision(hash: &str, fetched_at: DateTime<Utc>) -> Revision {
+        Revision {
+            hash: hash.to_owned(),
+            url: "https://example.com/hurdat2.txt".to_owned(),
+            fetched_at,
+            last_modified: None,
+            etag: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn history() {
+        let tmp = std::env::temp_dir().join(format!("cat5-history-{}", std::process::id()));
+        let client = reqwest::Client::new();
+        let dir = DataDir::create(&client, &tmp).await.unwrap();
+        let object = dir.get_object("hurdat2.txt");
+
+        // An object fetched before histories were kept has its current
+        // revision as its only one.
+        fs::write(tmp.join("abc123"), "v1").await.unwrap();
+        symlink("abc123", object.path()).await.unwrap();
+        let history = object.history().await.unwrap();
+        assert_eq!(
+            history.iter().map(|r| r.hash()).collect::<Vec<_>>(),
+            vec!["abc123"]
+        );
+
+        let t = Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap();
+        for r in [
+            revision("abc123", t),
+            revision("abd456", t + Duration::days(30)),
+            revision("ef7890", t + Duration::days(60)),
+        ] {
+            object.append_history(&r).await.unwrap();
+        }
+
+        let at = |t| {
+            let object = &object;
+            async move { object.revision_at(t).await.unwrap().map(|r| r.hash) }
+        };
+        assert_eq!(at(t - Duration::seconds(1)).await, None);
+        assert_eq!(at(t).await.as_deref(), Some("abc123"));
+        assert_eq!(at(t + Duration::days(59)).await.as_deref(), Some("abd456"));
+        assert_eq!(at(t + Duration::days(60)).await.as_deref(), Some("ef7890"));
+
+        assert_eq!(object.pin("ef").await.unwrap().hash(), "ef7890");
+        assert_eq!(object.current().await.unwrap().as_deref(), Some("ef7890"));
+        assert_eq!(
+            object.pin("ab").await.unwrap_err().kind(),
+            std::io::ErrorKind::InvalidInput
+        );
+        assert_eq!(
+            object.pin("999").await.unwrap_err().kind(),
+            std::io::ErrorKind::NotFound
+        );
+        assert_eq!(object.current().await.unwrap().as_deref(), Some("ef7890"));
+
+        fs::remove_dir_all(&tmp).await.unwrap();
+    }
+}
@@ -0,0 +1,450 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    atcf,
+    hurdat2::{Storm, TrackEntry, WindRadii},
+};
+
+// Tolerances are the largest differences between two values of a field that
+// are not reported as changes.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Tolerances {
+    position: f64,
+    wind: i32,
+    pressure: i32,
+    radii: i32,
+}
+
+impl Tolerances {
+    // new creates tolerances for position and radii in nautical miles, wind
+    // in knots and pressure in millibars.
+    pub fn new(position: f64, wind: i32, pressure: i32, radii: i32) -> Tolerances {
+        Tolerances {
+            position,
+            wind,
+            pressure,
+            radii,
+        }
+    }
+
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    pub fn wind(&self) -> i32 {
+        self.wind
+    }
+
+    pub fn pressure(&self) -> i32 {
+        self.pressure
+    }
+
+    pub fn radii(&self) -> i32 {
+        self.radii
+    }
+}
+
+// Diff is the difference between two revisions of a HURDAT2 dataset, with
+// storms matched by ATCF id.
+#[derive(Debug, Clone, Serialize)]
+pub struct Diff {
+    added: Vec<StormRef>,
+    removed: Vec<StormRef>,
+    changed: Vec<StormDiff>,
+}
+
+// StormRef identifies a storm that was added or removed.
+#[derive(Debug, Clone, Serialize)]
+pub struct StormRef {
+    id: atcf::Id,
+    name: Option<String>,
+}
+
+impl StormRef {
+    fn new(storm: &Storm) -> StormRef {
+        StormRef {
+            id: storm.id().clone(),
+            name: storm.name().map(|n| n.to_owned()),
+        }
+    }
+
+    pub fn id(&self) -> &atcf::Id {
+        &self.id
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+}
+
+// StormDiff is the set of changes to a storm present in both revisions.
+#[derive(Debug, Clone, Serialize)]
+pub struct StormDiff {
+    id: atcf::Id,
+    old_name: Option<String>,
+    new_name: Option<String>,
+    added_entries: Vec<DateTime<Utc>>,
+    removed_entries: Vec<DateTime<Utc>>,
+    changes: Vec<Change>,
+}
+
+impl StormDiff {
+    pub fn id(&self) -> &atcf::Id {
+        &self.id
+    }
+
+    pub fn old_name(&self) -> Option<&str> {
+        self.old_name.as_deref()
+    }
+
+    pub fn new_name(&self) -> Option<&str> {
+        self.new_name.as_deref()
+    }
+
+    pub fn is_renamed(&self) -> bool {
+        self.old_name != self.new_name
+    }
+
+    // added_entries are the times of track entries only in the new revision.
+    pub fn added_entries(&self) -> &[DateTime<Utc>] {
+        &self.added_entries
+    }
+
+    // removed_entries are the times of track entries only in the old revision.
+    pub fn removed_entries(&self) -> &[DateTime<Utc>] {
+        &self.removed_entries
+    }
+
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.is_renamed()
+            && self.added_entries.is_empty()
+            && self.removed_entries.is_empty()
+            && self.changes.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Field {
+    Time,
+    Status,
+    Position,
+    Wind,
+    Pressure,
+    Radii34,
+    Radii50,
+    Radii64,
+}
+
+impl Field {
+    pub fn to_str(&self) -> &str {
+        match self {
+            Field::Time => "time",
+            Field::Status => "status",
+            Field::Position => "position",
+            Field::Wind => "wind",
+            Field::Pressure => "pressure",
+            Field::Radii34 => "radii_34",
+            Field::Radii50 => "radii_50",
+            Field::Radii64 => "radii_64",
+        }
+    }
+}
+
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
+// Change is a change to a single field of a track entry. The time is that of
+// the entry in the old revision.
+#[derive(Debug, Clone, Serialize)]
+pub struct Change {
+    time: DateTime<Utc>,
+    field: Field,
+    #[serde(rename = "old")]
+    old_value: String,
+    #[serde(rename = "new")]
+    new_value: String,
+}
+
+impl Change {
+    pub fn time(&self) -> DateTime<Utc> {
+        self.time
+    }
+
+    pub fn field(&self) -> Field {
+        self.field
+    }
+
+    pub fn old_value(&self) -> &str {
+        &self.old_value
+    }
+
+    pub fn new_value(&self) -> &str {
+        &self.new_value
+    }
+}
+
+impl Diff {
+    // between compares the storms of two revisions. Track entries are matched
+    // by time. Entries left unmatched on both sides are paired up in order
+    // and reported as a change of time, along with any other changes, while
+    // the rest are reported as added or removed.
+    pub fn between(old: &[Storm], new: &[Storm], tolerances: &Tolerances) -> Diff {
+        let old_by_id = old.iter().map(|s| (s.id(), s)).collect::<HashMap<_, _>>();
+        let new_by_id = new.iter().map(|s| (s.id(), s)).collect::<HashMap<_, _>>();
+
+        let added = new
+            .iter()
+            .filter(|s| !old_by_id.contains_key(s.id()))
+            .map(StormRef::new)
+            .collect();
+        let removed = old
+            .iter()
+            .filter(|s| !new_by_id.contains_key(s.id()))
+            .map(StormRef::new)
+            .collect();
+        let changed = old
+            .iter()
+            .filter_map(|a| {
+                let b = new_by_id.get(a.id())?;
+                Some(diff_storms(a, b, tolerances)).filter(|d| !d.is_empty())
+            })
+            .collect();
+
+        Diff {
+            added,
+            removed,
+            changed,
+        }
+    }
+
+    pub fn added(&self) -> &[StormRef] {
+        &self.added
+    }
+
+    pub fn removed(&self) -> &[StormRef] {
+        &self.removed
+    }
+
+    pub fn changed(&self) -> &[StormDiff] {
+        &self.changed
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn diff_storms(old: &Storm, new: &Storm, tolerances: &Tolerances) -> StormDiff {
+    let mut entries: BTreeMap<DateTime<Utc>, (Option<&TrackEntry>, Option<&TrackEntry>)> =
+        BTreeMap::new();
+    for e in old.track() {
+        entries.entry(e.time()).or_default().0 = Some(e);
+    }
+    for e in new.track() {
+        entries.entry(e.time()).or_default().1 = Some(e);
+    }
+
+    let mut changes = vec![];
+    let mut removed = vec![];
+    let mut added = vec![];
+    for (a, b) in entries.values() {
+        match (a, b) {
+            (Some(a), Some(b)) => diff_entries(a, b, tolerances, &mut changes),
+            (Some(a), None) => removed.push(*a),
+            (None, Some(b)) => added.push(*b),
+            (None, None) => {}
+        }
+    }
+
+    let paired = removed.len().min(added.len());
+    for (a, b) in removed.drain(..paired).zip(added.drain(..paired)) {
+        changes.push(Change {
+            time: a.time(),
+            field: Field::Time,
+            old_value: format_time(a.time()),
+            new_value: format_time(b.time()),
+        });
+        diff_entries(a, b, tolerances, &mut changes);
+    }
+    changes.sort_by_key(|c| c.time);
+
+    StormDiff {
+        id: old.id().clone(),
+        old_name: old.name().map(|n| n.to_owned()),
+        new_name: new.name().map(|n| n.to_owned()),
+        added_entries: added.iter().map(|e| e.time()).collect(),
+        removed_entries: removed.iter().map(|e| e.time()).collect(),
+        changes,
+    }
+}
+
+fn diff_entries(
+    a: &TrackEntry,
+    b: &TrackEntry,
+    tolerances: &Tolerances,
+    changes: &mut Vec<Change>,
+) {
+    let mut push = |field, old_value, new_value| {
+        changes.push(Change {
+            time: a.time(),
+            field,
+            old_value,
+            new_value,
+        })
+    };
+
+    if a.status() != b.status() {
+        push(
+            Field::Status,
+            a.status().to_str().to_owned(),
+            b.status().to_str().to_owned(),
+        );
+    }
+
+    let moved = crate::geo::Location::distance_between(a.location(), b.location());
+    if moved.in_nautical_miles() > tolerances.position {
+        push(
+            Field::Position,
+            format_location(a.location()),
+            format_location(b.location()),
+        );
+    }
+
+    if (a.max_sustained_wind() - b.max_sustained_wind()).abs() > tolerances.wind {
+        push(
+            Field::Wind,
+            a.max_sustained_wind().to_string(),
+            b.max_sustained_wind().to_string(),
+        );
+    }
+
+    let pressure_changed = match (a.min_pressure(), b.min_pressure()) {
+        (Some(x), Some(y)) => (x - y).abs() > tolerances.pressure,
+        (x, y) => x != y,
+    };
+    if pressure_changed {
+        push(
+            Field::Pressure,
+            format_optional(a.min_pressure()),
+            format_optional(b.min_pressure()),
+        );
+    }
+
+    for (field, ra, rb) in [
+        (Field::Radii34, a.wind_radii_34kts(), b.wind_radii_34kts()),
+        (Field::Radii50, a.wind_radii_50kts(), b.wind_radii_50kts()),
+        (Field::Radii64, a.wind_radii_64kts(), b.wind_radii_64kts()),
+    ] {
+        let (qa, qb) = (quadrants(ra), quadrants(rb));
+        let changed = qa.iter().zip(qb.iter()).any(|pair| match pair {
+            (Some(x), Some(y)) => (x - y).abs() > tolerances.radii,
+            (x, y) => x != y,
+        });
+        if changed {
+            push(field, format_radii(&qa), format_radii(&qb));
+        }
+    }
+}
+
+fn quadrants(r: &WindRadii) -> [Option<i32>; 4] {
+    [r.ne(), r.se(), r.sw(), r.nw()].map(|q| q.map(|d| d.in_nautical_miles().round() as i32))
+}
+
+fn format_time(t: DateTime<Utc>) -> String {
+    t.format("%Y-%m-%d %H:%MZ").to_string()
+}
+
+fn format_location(l: &crate::geo::Location) -> String {
+    format!("{:.1},{:.1}", l.lat(), l.lng())
+}
+
+fn format_optional(v: Option<i32>) -> String {
+    v.map_or_else(|| "-".to_owned(), |v| v.to_string())
+}
+
+fn format_radii(q: &[Option<i32>; 4]) -> String {
+    q.iter()
+        .map(|&r| format_optional(r))
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Diff, Field, Tolerances};
+    use crate::hurdat2::{Hurdat2Reader, ParseMode, Storm};
+
+    const OLD: &str = "\
+AL012020,            UNNAMED,      2,
+20200901, 0000,  , TS, 20.0N,  60.0W,  40, 1000,   30,   30,    0,    0,    0,    0,    0,    0,    0,    0,    0,    0, -999,
+20200901, 0600,  , TS, 20.5N,  61.0W,  45,  998,   30,   30,    0,    0,    0,    0,    0,    0,    0,    0,    0,    0, -999,
+AL022020,             BERTHA,      1,
+20200905, 0000,  , TD, 25.0N,  70.0W,  30, 1008, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+";
+
+    const NEW: &str = "\
+AL012020,             ARTHUR,      3,
+20200901, 0000,  , TS, 20.0N,  60.1W,  45, 1001,   30,   40,    0,    0,    0,    0,    0,    0,    0,    0,    0,    0, -999,
+20200901, 0900,  , TS, 20.5N,  61.0W,  45,  998,   30,   30,    0,    0,    0,    0,    0,    0,    0,    0,    0,    0, -999,
+20200901, 1200,  , TS, 21.0N,  62.0W,  50,  995,   30,   30,    0,    0,    0,    0,    0,    0,    0,    0,    0,    0, -999,
+AL032020,          CRISTOBAL,      1,
+20200910, 0000,  , TD, 25.0N,  70.0W,  30, 1008, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+";
+
+    fn read(s: &str) -> Vec<Storm> {
+        Hurdat2Reader::new(s.as_bytes(), ParseMode::Strict)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn diff() {
+        let (old, new) = (read(OLD), read(NEW));
+
+        let d = Diff::between(&old, &new, &Tolerances::default());
+        assert_eq!(d.added().len(), 1);
+        assert_eq!(d.added()[0].name(), Some("CRISTOBAL"));
+        assert_eq!(d.removed().len(), 1);
+        assert_eq!(d.removed()[0].name(), Some("BERTHA"));
+        assert_eq!(d.changed().len(), 1);
+
+        let s = &d.changed()[0];
+        assert!(s.is_renamed());
+        assert_eq!(s.new_name(), Some("ARTHUR"));
+        assert_eq!(s.added_entries().len(), 1);
+        assert!(s.removed_entries().is_empty());
+        let fields = s.changes().iter().map(|c| c.field()).collect::<Vec<_>>();
+        assert_eq!(
+            fields,
+            vec![
+                Field::Position,
+                Field::Wind,
+                Field::Pressure,
+                Field::Radii34,
+                Field::Time
+            ]
+        );
+        assert_eq!(s.changes()[1].old_value(), "40");
+        assert_eq!(s.changes()[1].new_value(), "45");
+        assert_eq!(s.changes()[3].new_value(), "30/40/0/0");
+
+        let d = Diff::between(&old, &new, &Tolerances::new(10.0, 5, 1, 10));
+        let fields = d.changed()[0]
+            .changes()
+            .iter()
+            .map(|c| c.field())
+            .collect::<Vec<_>>();
+        assert_eq!(fields, vec![Field::Time]);
+    }
+}
@@ -0,0 +1,151 @@
+use std::{io::Write, path::PathBuf};
+
+use crate::{
+    diff::{Diff, Tolerances},
+    hurdat2::ParseMode,
+    ibtracs, source, DataDir, Error,
+};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[clap(help = "old revision, either a path or the name or hash of an object in the data dir")]
+    old: String,
+
+    #[clap(help = "new revision, either a path or the name or hash of an object in the data dir")]
+    new: String,
+
+    #[clap(long, value_enum, default_value_t = Format::Text, help = "output format")]
+    format: Format,
+
+    #[clap(
+        long,
+        default_value_t = ibtracs::Agency::default(),
+        help = "agency whose positions and winds are compared in IBTrACS data"
+    )]
+    agency: ibtracs::Agency,
+
+    #[clap(
+        long,
+        default_value_t = 0.0,
+        help = "ignore position changes up to this distance (nm)"
+    )]
+    position_tolerance: f64,
+
+    #[clap(
+        long,
+        default_value_t = 0,
+        help = "ignore wind changes up to this many kt"
+    )]
+    wind_tolerance: i32,
+
+    #[clap(
+        long,
+        default_value_t = 0,
+        help = "ignore pressure changes up to this many mb"
+    )]
+    pressure_tolerance: i32,
+
+    #[clap(
+        long,
+        default_value_t = 0,
+        help = "ignore wind radii changes up to this many nm"
+    )]
+    radii_tolerance: i32,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+pub async fn run(dir: &DataDir<'_>, args: &Args) -> Result<(), Error> {
+    let old = source::load_path(
+        dir,
+        resolve(dir, &args.old),
+        args.agency,
+        ParseMode::Lenient,
+        |_| true,
+    )
+    .await?;
+    let new = source::load_path(
+        dir,
+        resolve(dir, &args.new),
+        args.agency,
+        ParseMode::Lenient,
+        |_| true,
+    )
+    .await?;
+
+    let tolerances = Tolerances::new(
+        args.position_tolerance,
+        args.wind_tolerance,
+        args.pressure_tolerance,
+        args.radii_tolerance,
+    );
+    let diff = Diff::between(&old, &new, &tolerances);
+
+    let mut w = std::io::stdout().lock();
+    match args.format {
+        Format::Text => write_text(&mut w, &diff)?,
+        Format::Json => {
+            serde_json::to_writer(&mut w, &diff)?;
+            writeln!(w)?;
+        }
+    }
+
+    Ok(())
+}
+
+// resolve returns the path of an object in the data dir if one exists with
+// the given name, which includes the content hashes of fetched files.
+// Otherwise, the name is taken to be a path.
+fn resolve(dir: &DataDir<'_>, name: &str) -> PathBuf {
+    let path = dir.get_object(name).path();
+    if path.exists() {
+        path
+    } else {
+        PathBuf::from(name)
+    }
+}
+
+fn write_text<W: Write>(w: &mut W, diff: &Diff) -> Result<(), Error> {
+    let name = |n: Option<&str>| n.unwrap_or("UNNAMED").to_owned();
+    for s in diff.added() {
+        writeln!(w, "+ {} {}", s.id(), name(s.name()))?;
+    }
+    for s in diff.removed() {
+        writeln!(w, "- {} {}", s.id(), name(s.name()))?;
+    }
+    for s in diff.changed() {
+        writeln!(w, "~ {} {}", s.id(), name(s.new_name()))?;
+        if s.is_renamed() {
+            writeln!(
+                w,
+                "    name: {} -> {}",
+                name(s.old_name()),
+                name(s.new_name())
+            )?;
+        }
+        for t in s.added_entries() {
+            writeln!(w, "    + {}", t.format("%Y-%m-%d %H:%MZ"))?;
+        }
+        for t in s.removed_entries() {
+            writeln!(w, "    - {}", t.format("%Y-%m-%d %H:%MZ"))?;
+        }
+        for c in s.changes() {
+            writeln!(
+                w,
+                "    {} {}: {} -> {}",
+                c.time().format("%Y-%m-%d %H:%MZ"),
+                c.field(),
+                c.old_value(),
+                c.new_value()
+            )?;
+        }
+    }
+    if diff.is_empty() {
+        writeln!(w, "no changes")?;
+    }
+    Ok(())
+}
@@ -0,0 +1,81 @@
+use std::io;
+
+use thiserror::Error;
+
+use crate::{data_dir::FetchError, hurdat2, map::MapError};
+
+// Error is the union of everything that can go wrong in cat5. Library
+// functions return the narrowest error type that applies; each converts into
+// this type so that callers can use ? across modules and still match on the
+// kind of failure.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error(transparent)]
+    Parse(#[from] hurdat2::ParseError),
+
+    #[error(transparent)]
+    Value(#[from] ValueError),
+
+    #[error(transparent)]
+    Fetch(#[from] FetchError),
+
+    #[error(transparent)]
+    Map(#[from] MapError),
+
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+
+    #[error(transparent)]
+    CsvAsync(#[from] csv_async::Error),
+
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+
+    #[error("{0}")]
+    Usage(String),
+
+    #[error("validation found {0} errors")]
+    Invalid(usize),
+}
+
+// ValueError indicates that a string could not be parsed into one of the
+// crate's value types, e.g. a basin, a status or a location.
+#[derive(Debug, Clone, Error)]
+#[error("invalid {kind}: {value}")]
+pub struct ValueError {
+    kind: &'static str,
+    value: String,
+}
+
+impl ValueError {
+    pub(crate) fn new(kind: &'static str, value: &str) -> ValueError {
+        ValueError {
+            kind,
+            value: value.to_owned(),
+        }
+    }
+
+    pub fn kind(&self) -> &str {
+        self.kind
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Error, ValueError};
+
+    fn is_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn send_sync() {
+        is_send_sync::<Error>();
+        is_send_sync::<ValueError>();
+    }
+}
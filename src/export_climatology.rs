@@ -0,0 +1,71 @@
+use tokio::fs;
+
+use crate::{
+    climatology::{self, Climatology},
+    filter::Filter,
+    source::StormSource,
+    DataDir, Error,
+};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[clap(help = "path to where the output file should be written")]
+    dst: String,
+
+    #[clap(flatten)]
+    source: StormSource,
+
+    #[clap(
+        long = "filter",
+        default_value = "all",
+        help = "only include storms matching the filter expression, may be repeated"
+    )]
+    filters: Vec<Filter>,
+
+    #[clap(
+        long,
+        help = "first year to average over, defaults to the earliest storm"
+    )]
+    start_year: Option<i32>,
+
+    #[clap(long, help = "last year to average over, defaults to the latest storm")]
+    end_year: Option<i32>,
+
+    #[clap(
+        long,
+        default_value_t = 1,
+        help = "width, in days, of the moving average used to smooth the curves"
+    )]
+    smooth: usize,
+}
+
+pub async fn run(dir: &DataDir<'_>, args: &Args) -> Result<(), Error> {
+    // Even widths are rounded up to the next odd number of days, which must
+    // still fit within the year.
+    if args.smooth >= climatology::DAYS {
+        return Err(Error::Usage(format!(
+            "--smooth must be at most {} days",
+            climatology::DAYS - 1
+        )));
+    }
+
+    let storms = args
+        .source
+        .load(dir, |s| args.filters.iter().all(|f| f.matches(s)))
+        .await?;
+
+    let years = storms.iter().map(|s| s.id().year());
+    let start_year = args.start_year.or(years.clone().min()).unwrap_or_default();
+    let end_year = args.end_year.or(years.max()).unwrap_or_default();
+    if start_year > end_year {
+        return Err(Error::Usage(format!(
+            "start year {} is after end year {}",
+            start_year, end_year
+        )));
+    }
+
+    let climatology = Climatology::from_storms(&storms, start_year, end_year).smoothed(args.smooth);
+    fs::write(&args.dst, serde_json::to_vec(&climatology)?).await?;
+
+    Ok(())
+}
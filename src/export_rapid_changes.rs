@@ -0,0 +1,137 @@
+use chrono::Duration;
+use tokio::fs;
+
+use crate::{
+    filter::Filter,
+    intensity::{self, Thresholds},
+    source::StormSource,
+    DataDir, Error,
+};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[clap(help = "path to where the output file should be written")]
+    dst: String,
+
+    #[clap(flatten)]
+    source: StormSource,
+
+    #[clap(
+        long = "filter",
+        default_value = "all",
+        help = "only search storms matching the filter expression, may be repeated"
+    )]
+    filters: Vec<Filter>,
+
+    #[clap(long, value_enum, default_value_t = Format::Json, help = "format of the output file")]
+    format: Format,
+
+    #[clap(
+        long,
+        default_value_t = 30,
+        help = "minimum increase in wind (kt) over the window for rapid intensification"
+    )]
+    intensification: i32,
+
+    #[clap(
+        long,
+        default_value_t = 30,
+        help = "minimum decrease in wind (kt) over the window for rapid weakening"
+    )]
+    weakening: i32,
+
+    #[clap(
+        long,
+        default_value_t = 24,
+        help = "length of the window, in hours, over which wind changes are measured"
+    )]
+    window_hours: i64,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum Format {
+    Json,
+    Csv,
+}
+
+// MAX_WINDOW_HOURS bounds --window-hours. Rapid changes are measured over a
+// day or two; a window longer than a week would span most storms' lifetimes.
+const MAX_WINDOW_HOURS: i64 = 7 * 24;
+
+pub async fn run(dir: &DataDir<'_>, args: &Args) -> Result<(), Error> {
+    let window = match Duration::try_hours(args.window_hours) {
+        Some(window) if args.window_hours > 0 && args.window_hours <= MAX_WINDOW_HOURS => window,
+        _ => {
+            return Err(Error::Usage(format!(
+                "--window-hours must be between 1 and {}, got {}",
+                MAX_WINDOW_HOURS, args.window_hours
+            )))
+        }
+    };
+    let thresholds = Thresholds::new(args.intensification, args.weakening, window);
+
+    let storms = args
+        .source
+        .load(dir, |s| args.filters.iter().all(|f| f.matches(s)))
+        .await?;
+
+    let mut events = vec![];
+    for storm in &storms {
+        let found = intensity::find_events(storm, &thresholds);
+        for time in intensity::untagged_rapid_changes(storm, &found) {
+            eprintln!(
+                "{}: rapid changes indicator at {} not within any detected event",
+                storm.id(),
+                time.format("%Y-%m-%d %H:%MZ")
+            );
+        }
+        events.extend(found);
+    }
+
+    let contents = match args.format {
+        Format::Json => serde_json::to_vec(&events)?,
+        Format::Csv => to_csv(&events)?,
+    };
+
+    fs::write(&args.dst, contents).await?;
+
+    Ok(())
+}
+
+fn to_csv(events: &[intensity::Event]) -> Result<Vec<u8>, Error> {
+    let mut w = csv::Writer::from_writer(Vec::new());
+    w.write_record([
+        "storm",
+        "name",
+        "kind",
+        "start",
+        "end",
+        "start_lat",
+        "start_lng",
+        "end_lat",
+        "end_lng",
+        "delta_wind",
+        "delta_pressure",
+        "tagged",
+    ])?;
+    for event in events {
+        w.write_record([
+            event.storm().to_string(),
+            event.name().unwrap_or_default().to_owned(),
+            event.kind().to_string(),
+            event.start().to_rfc3339(),
+            event.end().to_rfc3339(),
+            event.start_location().lat().to_string(),
+            event.start_location().lng().to_string(),
+            event.end_location().lat().to_string(),
+            event.end_location().lng().to_string(),
+            event.delta_wind().to_string(),
+            event
+                .delta_pressure()
+                .map(|p| p.to_string())
+                .unwrap_or_default(),
+            event.tagged().to_string(),
+        ])?;
+    }
+    w.into_inner().map_err(|e| Error::Io(e.into_error()))
+}
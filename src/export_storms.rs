@@ -1,54 +1,88 @@
-use std::error::Error;
+use serde::Serialize;
+use tokio::fs;
 
-use csv_async::AsyncReaderBuilder;
-use csv_async::StringRecordsStream;
-use tokio::{fs, io};
-
-use crate::hurdat2::{Status, Storm};
+use crate::{
+    filter::Filter,
+    hurdat2::{Hurdat2Writer, Metrics, Motion, Storm},
+    source::StormSource,
+    DataDir, Error,
+};
 
 #[derive(Debug, clap::Args)]
 pub struct Args {
-    #[clap(help = "CSV file containing hurdat2 data")]
-    src: String,
-
-    #[clap(help = "path to where the JSON output file should be written")]
-    dst: String,
-}
+    #[clap(
+        value_name = "[SRC] DST",
+        num_args = 1..=2,
+        required = true,
+        help = "file to read storms from, as with --src, and the path to where the output file should be written"
+    )]
+    paths: Vec<String>,
 
-pub async fn run(args: &Args) -> Result<(), Box<dyn Error>> {
-    let r = fs::File::open(&args.src).await?;
+    #[clap(flatten)]
+    source: StormSource,
 
-    let mut stream = AsyncReaderBuilder::new()
-        .flexible(true)
-        .has_headers(false)
-        .create_reader(r);
-    let mut records = stream.records();
-    let storms = collect_storms(&mut records, |s| {
-        s.track().iter().any(|e| e.status() == Status::Hurricane)
-    })
-    .await?;
+    #[clap(
+        long = "filter",
+        default_value = "status:HU",
+        help = "only export storms matching the filter expression, may be repeated"
+    )]
+    filters: Vec<Filter>,
 
-    let json = serde_json::to_string(&storms)?;
+    #[clap(long, value_enum, default_value_t = Format::Json, help = "format of the output file")]
+    format: Format,
+}
 
-    fs::write(&args.dst, json).await?;
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum Format {
+    Json,
+    Hurdat2,
+}
 
-    Ok(())
+// StormWithMetrics is the JSON representation of an exported storm.
+#[derive(Serialize)]
+struct StormWithMetrics<'a> {
+    #[serde(flatten)]
+    storm: &'a Storm,
+    metrics: Metrics,
+    motion: Vec<Motion>,
 }
 
-async fn collect_storms<'a, R, F>(
-    stream: &mut StringRecordsStream<'a, R>,
-    filter: F,
-) -> Result<Vec<Storm>, Box<dyn Error>>
-where
-    R: io::AsyncRead + Unpin + Send,
-    F: Fn(&Storm) -> bool,
-{
-    let mut storms = vec![];
-    while let Some(storm) = Storm::from_record_stream(stream).await {
-        let storm = storm?;
-        if filter(&storm) {
-            storms.push(storm);
+pub async fn run(dir: &DataDir<'_>, args: &Args) -> Result<(), Error> {
+    let (source, dst) = match &args.paths[..] {
+        [src, dst] if args.source.is_default() => (args.source.with_src(src), dst),
+        [_, _] => {
+            return Err(Error::Usage(
+                "a source file can't be given with --dataset, --src or --as-of".into(),
+            ))
         }
-    }
-    Ok(storms)
+        [dst] => (args.source.clone(), dst),
+        _ => unreachable!("clap takes one or two paths"),
+    };
+    let storms = source
+        .load(dir, |s| args.filters.iter().all(|f| f.matches(s)))
+        .await?;
+
+    let contents = match args.format {
+        Format::Json => serde_json::to_vec(
+            &storms
+                .iter()
+                .map(|storm| StormWithMetrics {
+                    storm,
+                    metrics: storm.metrics(),
+                    motion: storm.motion(),
+                })
+                .collect::<Vec<_>>(),
+        )?,
+        Format::Hurdat2 => {
+            let mut w = Hurdat2Writer::new(Vec::new());
+            for storm in &storms {
+                w.write(storm)?;
+            }
+            w.into_inner()
+        }
+    };
+
+    fs::write(dst, contents).await?;
+
+    Ok(())
 }
@@ -0,0 +1,101 @@
+use chrono::Duration;
+use serde_json::json;
+use tokio::fs;
+
+use crate::{filter::Filter, map::Map, source::StormSource, DataDir, Error, ValueError};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[clap(help = "path to where the GeoJSON file should be written")]
+    dst: String,
+
+    #[clap(flatten)]
+    source: StormSource,
+
+    #[clap(
+        long = "filter",
+        default_value = "landfall",
+        help = "only export storms matching the filter expression, may be repeated"
+    )]
+    filters: Vec<Filter>,
+
+    #[clap(
+        long = "wind",
+        value_parser = parse_wind,
+        default_values = ["34", "50", "64"],
+        help = "wind speed (kt) of the swath, one of 34, 50 or 64, may be repeated"
+    )]
+    winds: Vec<i32>,
+
+    #[clap(
+        long,
+        default_value_t = 60,
+        help = "interval, in minutes, at which wind radii are interpolated along the track"
+    )]
+    interval_minutes: i64,
+
+    #[clap(
+        long,
+        help = "map.json, as written by update-data, whose bins within each swath are added to its properties"
+    )]
+    map: Option<String>,
+}
+
+fn parse_wind(s: &str) -> Result<i32, ValueError> {
+    match s {
+        "34" | "50" | "64" => Ok(s.parse().unwrap()),
+        _ => Err(ValueError::new("wind", s)),
+    }
+}
+
+pub async fn run(dir: &DataDir<'_>, args: &Args) -> Result<(), Error> {
+    let interval = match Duration::try_minutes(args.interval_minutes) {
+        Some(interval) if interval > Duration::zero() => interval,
+        _ => {
+            return Err(Error::Usage(format!(
+                "--interval-minutes must be positive, got {}",
+                args.interval_minutes
+            )))
+        }
+    };
+    let map = match &args.map {
+        Some(path) => Some(serde_json::from_slice::<Map>(&fs::read(path).await?)?),
+        None => None,
+    };
+
+    let storms = args
+        .source
+        .load(dir, |s| args.filters.iter().all(|f| f.matches(s)))
+        .await?;
+
+    let mut features = vec![];
+    for storm in &storms {
+        for &wind in &args.winds {
+            let swath = storm.wind_swath(wind, interval);
+            if swath.is_empty() {
+                continue;
+            }
+            let mut properties = json!({
+                "id": storm.id(),
+                "name": storm.name(),
+                "wind": wind,
+            });
+            if let Some(map) = &map {
+                properties["bins"] = json!(swath.bins(map));
+            }
+            features.push(json!({
+                "type": "Feature",
+                "geometry": swath.to_geojson(),
+                "properties": properties,
+            }));
+        }
+    }
+
+    let collection = json!({
+        "type": "FeatureCollection",
+        "features": features,
+    });
+    fs::write(&args.dst, serde_json::to_vec(&collection)?).await?;
+
+    Ok(())
+}
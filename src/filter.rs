@@ -0,0 +1,403 @@
+use std::{fmt, ops, str::FromStr};
+
+use chrono::Datelike;
+
+use crate::{
+    atcf::Basin,
+    hurdat2::{Category, Indicator, Status, Storm},
+    ValueError,
+};
+
+// Filter is a predicate over storms. Filters can be built directly or parsed
+// from expressions such as:
+//
+//   basin:AL and year:1990-2020 and (category:3 or landfall) and not name:UNNAMED
+//
+// The terms are:
+//
+//   all                  every storm
+//   basin:<basin>        storms numbered in the basin, e.g. AL or EP
+//   in:<basin>           storms with at least one fix inside the basin's
+//                        boundary, wherever they were numbered
+//   cross-basin          storms with a fix outside the basin they were
+//                        numbered in
+//   year:<y>[-<y>]       storms whose season falls in the inclusive range
+//   month:<m>[-<m>]      storms that formed in the month range (which may wrap
+//                        around the end of the year, e.g. 11-2)
+//   category:<c>         storms that reached at least the Saffir-Simpson
+//                        category (TD, TS, 1 through 5)
+//   name:<glob>          storms whose name matches the glob, ignoring case
+//   status:<status>      storms that ever had the status, e.g. HU or SS
+//   landfall             storms with at least one landfall
+//   bbox:<s>,<w>,<n>,<e> storms with at least one fix inside the box
+//
+// Terms combine with "and", "or", "not" and parentheses. "and" binds more
+// tightly than "or".
+#[derive(Debug, Clone)]
+pub enum Filter {
+    All,
+    Basin(Basin),
+    In(Basin),
+    CrossBasin,
+    Years(i32, i32),
+    Months(u32, u32),
+    MinCategory(Category),
+    Name(String),
+    Status(Status),
+    Landfall,
+    BoundingBox(BoundingBox),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn matches(&self, storm: &Storm) -> bool {
+        match self {
+            Filter::All => true,
+            Filter::Basin(basin) => storm.id().basin() == *basin,
+            Filter::In(basin) => storm.track().iter().any(|e| e.basin() == Some(*basin)),
+            Filter::CrossBasin => storm.crosses_basins(),
+            Filter::Years(from, to) => (*from..=*to).contains(&storm.id().year()),
+            Filter::Months(from, to) => storm.track().first().is_some_and(|e| {
+                let m = e.time().month();
+                if from <= to {
+                    (*from..=*to).contains(&m)
+                } else {
+                    m >= *from || m <= *to
+                }
+            }),
+            Filter::MinCategory(c) => storm.peak_category().is_some_and(|p| p >= *c),
+            Filter::Name(pattern) => storm.name().is_some_and(|n| glob_matches(pattern, n)),
+            Filter::Status(status) => storm.track().iter().any(|e| e.status() == *status),
+            Filter::Landfall => storm
+                .track()
+                .iter()
+                .any(|e| matches!(e.indicator(), Some(Indicator::Landfall))),
+            Filter::BoundingBox(bbox) => storm
+                .track()
+                .iter()
+                .any(|e| bbox.contains(e.location().lat(), e.location().lng())),
+            Filter::And(filters) => filters.iter().all(|f| f.matches(storm)),
+            Filter::Or(filters) => filters.iter().any(|f| f.matches(storm)),
+            Filter::Not(filter) => !filter.matches(storm),
+        }
+    }
+
+    pub fn and(self, other: Filter) -> Filter {
+        match self {
+            Filter::And(mut filters) => {
+                filters.push(other);
+                Filter::And(filters)
+            }
+            f => Filter::And(vec![f, other]),
+        }
+    }
+
+    pub fn or(self, other: Filter) -> Filter {
+        match self {
+            Filter::Or(mut filters) => {
+                filters.push(other);
+                Filter::Or(filters)
+            }
+            f => Filter::Or(vec![f, other]),
+        }
+    }
+
+    fn from_term(term: &str) -> Result<Filter, ValueError> {
+        let invalid = || ValueError::new("filter", term);
+        let (key, value) = term.split_once(':').unwrap_or((term, ""));
+        match (key.to_ascii_lowercase().as_str(), value) {
+            ("all", "") => Ok(Filter::All),
+            ("landfall", "") => Ok(Filter::Landfall),
+            ("cross-basin", "") => Ok(Filter::CrossBasin),
+            ("basin", v) => Ok(Filter::Basin(v.parse()?)),
+            ("in", v) => Ok(Filter::In(v.parse()?)),
+            ("year", v) => {
+                let (from, to) = parse_range(v).ok_or_else(invalid)?;
+                Ok(Filter::Years(from, to))
+            }
+            ("month", v) => match parse_range(v).ok_or_else(invalid)? {
+                (from, to) if (1..=12).contains(&from) && (1..=12).contains(&to) => {
+                    Ok(Filter::Months(from, to))
+                }
+                _ => Err(invalid()),
+            },
+            ("category", v) => Ok(Filter::MinCategory(v.to_ascii_uppercase().parse()?)),
+            ("name", v) if !v.is_empty() => Ok(Filter::Name(v.to_owned())),
+            ("status", v) => Ok(Filter::Status(v.to_ascii_uppercase().parse()?)),
+            ("bbox", v) => Ok(Filter::BoundingBox(v.parse()?)),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl ops::Not for Filter {
+    type Output = Filter;
+
+    fn not(self) -> Filter {
+        Filter::Not(Box::new(self))
+    }
+}
+
+impl FromStr for Filter {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens = tokenize(s);
+        let mut parser = Parser {
+            tokens: &tokens,
+            pos: 0,
+        };
+        let filter = parser.parse_or()?;
+        if parser.pos != tokens.len() {
+            return Err(ValueError::new("filter", s));
+        }
+        Ok(filter)
+    }
+}
+
+// BoundingBox is a region bounded by lines of latitude and longitude. If west
+// is greater than east, the box crosses the antimeridian.
+#[derive(Debug, Clone, Copy)]
+pub struct BoundingBox {
+    south: f64,
+    west: f64,
+    north: f64,
+    east: f64,
+}
+
+impl BoundingBox {
+    pub fn new(south: f64, west: f64, north: f64, east: f64) -> BoundingBox {
+        BoundingBox {
+            south,
+            west,
+            north,
+            east,
+        }
+    }
+
+    pub fn contains(&self, lat: f64, lng: f64) -> bool {
+        if lat < self.south || lat > self.north {
+            return false;
+        }
+        if self.west <= self.east {
+            lng >= self.west && lng <= self.east
+        } else {
+            lng >= self.west || lng <= self.east
+        }
+    }
+}
+
+impl fmt::Display for BoundingBox {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{},{},{},{}",
+            self.south, self.west, self.north, self.east
+        )
+    }
+}
+
+impl FromStr for BoundingBox {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let vals = s
+            .split(',')
+            .map(|v| v.trim().parse::<f64>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ValueError::new("bounding box", s))?;
+        match vals[..] {
+            [south, west, north, east] if south <= north => {
+                Ok(BoundingBox::new(south, west, north, east))
+            }
+            _ => Err(ValueError::new("bounding box", s)),
+        }
+    }
+}
+
+fn parse_range<T: FromStr + Copy>(s: &str) -> Option<(T, T)> {
+    match s.split_once('-') {
+        Some((from, to)) => Some((from.parse().ok()?, to.parse().ok()?)),
+        None => {
+            let v = s.parse().ok()?;
+            Some((v, v))
+        }
+    }
+}
+
+// glob_matches reports whether name matches pattern, where * matches any run
+// of characters and ? matches a single character. Case is ignored.
+fn glob_matches(pattern: &str, name: &str) -> bool {
+    let p = pattern.to_uppercase().chars().collect::<Vec<_>>();
+    let n = name.to_uppercase().chars().collect::<Vec<_>>();
+    let (mut pi, mut ni) = (0, 0);
+    let mut star = None;
+    while ni < n.len() {
+        match p.get(pi) {
+            Some('*') => {
+                star = Some((pi, ni));
+                pi += 1;
+            }
+            Some(c) if *c == '?' || *c == n[ni] => {
+                pi += 1;
+                ni += 1;
+            }
+            _ => match star {
+                Some((sp, sn)) => {
+                    pi = sp + 1;
+                    ni = sn + 1;
+                    star = Some((sp, sn + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    p[pi..].iter().all(|c| *c == '*')
+}
+
+fn tokenize(s: &str) -> Vec<String> {
+    let mut tokens = vec![];
+    let mut token = String::new();
+    for c in s.chars() {
+        match c {
+            '(' | ')' => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+                tokens.push(c.to_string());
+            }
+            c if c.is_whitespace() => {
+                if !token.is_empty() {
+                    tokens.push(std::mem::take(&mut token));
+                }
+            }
+            c => token.push(c),
+        }
+    }
+    if !token.is_empty() {
+        tokens.push(token);
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: &'a [String],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.pos).map(|t| t.as_str())
+    }
+
+    fn next(&mut self) -> Result<&'a str, ValueError> {
+        let t = self
+            .peek()
+            .ok_or_else(|| ValueError::new("filter", "unexpected end of expression"))?;
+        self.pos += 1;
+        Ok(t)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        self.peek().is_some_and(|t| t.eq_ignore_ascii_case(keyword))
+    }
+
+    fn parse_or(&mut self) -> Result<Filter, ValueError> {
+        let mut filter = self.parse_and()?;
+        while self.is_keyword("or") {
+            self.pos += 1;
+            filter = filter.or(self.parse_and()?);
+        }
+        Ok(filter)
+    }
+
+    fn parse_and(&mut self) -> Result<Filter, ValueError> {
+        let mut filter = self.parse_unary()?;
+        while self.is_keyword("and") {
+            self.pos += 1;
+            filter = filter.and(self.parse_unary()?);
+        }
+        Ok(filter)
+    }
+
+    fn parse_unary(&mut self) -> Result<Filter, ValueError> {
+        match self.next()? {
+            t if t.eq_ignore_ascii_case("not") => Ok(!self.parse_unary()?),
+            "(" => {
+                let filter = self.parse_or()?;
+                match self.next()? {
+                    ")" => Ok(filter),
+                    t => Err(ValueError::new("filter", t)),
+                }
+            }
+            t => Filter::from_term(t),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{glob_matches, Filter};
+    use crate::hurdat2::{Hurdat2Reader, ParseMode, Storm};
+
+    const SAMPLE: &str = "\
+AL011851,            UNNAMED,      2,
+18510625, 0000,  , HU, 28.0N,  94.8W,  80, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+18510625, 0600,  , HU, 28.0N,  95.4W,  80, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+AL092023,             IDALIA,      2,
+20230830, 0600,  , HU, 28.9N,  84.1W, 110,  942,  150,  130,   70,  100,   70,   60,   40,   50,   40,   30,   25,   30,   15,
+20230830, 1145, L, HU, 29.9N,  83.6W, 100,  949,  150,  130,   60,   90,   70,   60,   40,   50,   40,   30,   25,   30,   15,
+AL162016,               OTTO,      2,
+20161124, 1730, L, HU, 11.0N,  83.8W,  95,  975,   80,   60,   40,   60,   40,   30,   20,   30,   20,   15,   10,   15, -999,
+20161125, 1200,  , TS, 10.8N,  87.4W,  50,  997,  100,   70,   40,   60,   30,   30,    0,   20,    0,    0,    0,    0, -999,
+";
+
+    fn matching(expr: &str) -> Vec<String> {
+        let filter = expr.parse::<Filter>().unwrap();
+        Hurdat2Reader::new(SAMPLE.as_bytes(), ParseMode::Strict)
+            .map(|s| s.unwrap())
+            .filter(|s| filter.matches(s))
+            .map(|s: Storm| s.id().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn filters() {
+        assert_eq!(matching("all"), vec!["AL011851", "AL092023", "AL162016"]);
+        assert_eq!(matching("year:2000-2030"), vec!["AL092023", "AL162016"]);
+        assert_eq!(matching("category:3"), vec!["AL092023"]);
+        assert_eq!(
+            matching("category:3 or year:1851"),
+            vec!["AL011851", "AL092023"]
+        );
+        assert_eq!(
+            matching("landfall and basin:al"),
+            vec!["AL092023", "AL162016"]
+        );
+        assert_eq!(
+            matching("not (name:ida* or month:8-9)"),
+            vec!["AL011851", "AL162016"]
+        );
+        assert_eq!(matching("month:6"), vec!["AL011851"]);
+        assert_eq!(matching("bbox:27,-96,29,-95"), vec!["AL011851"]);
+        assert_eq!(matching("status:hu and not landfall"), vec!["AL011851"]);
+        assert_eq!(matching("in:ep"), vec!["AL162016"]);
+        assert_eq!(matching("cross-basin"), vec!["AL162016"]);
+        assert_eq!(matching("in:al and not cross-basin").len(), 2);
+
+        assert!("year:x".parse::<Filter>().is_err());
+        assert!("landfall and".parse::<Filter>().is_err());
+        assert!("(landfall".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn globs() {
+        assert!(glob_matches("kat*", "KATRINA"));
+        assert!(glob_matches("*RINA", "KATRINA"));
+        assert!(glob_matches("K?TRINA", "KATRINA"));
+        assert!(!glob_matches("K?RINA", "KATRINA"));
+        assert!(glob_matches("*", ""));
+    }
+}
@@ -0,0 +1,155 @@
+use std::{collections::HashMap, io::BufRead};
+
+use chrono::{DateTime, Datelike, Duration, Utc};
+use serde::Serialize;
+
+use crate::{
+    atcf::{self, Record},
+    geo, Error,
+};
+
+// Forecast is a single run of a forecast aid for a storm: the positions and
+// intensities that one technique predicted from one initial time.
+#[derive(Debug, Clone, Serialize)]
+pub struct Forecast {
+    storm: atcf::Id,
+    technique: String,
+    init: DateTime<Utc>,
+    points: Vec<ForecastPoint>,
+}
+
+impl Forecast {
+    pub fn storm(&self) -> &atcf::Id {
+        &self.storm
+    }
+
+    // technique is the ATCF name of the aid, e.g. OFCL for the official
+    // forecast or GFSI for the interpolated GFS.
+    pub fn technique(&self) -> &str {
+        &self.technique
+    }
+
+    pub fn init(&self) -> DateTime<Utc> {
+        self.init
+    }
+
+    // points returns the forecast at each lead time, in order.
+    pub fn points(&self) -> &[ForecastPoint] {
+        &self.points
+    }
+
+    // read_a_deck reads the forecasts in an ATCF a-deck such as
+    // aal092023.dat. Records with negative lead times, which hold the
+    // positions preceding the initial time, are ignored.
+    pub fn read_a_deck<R: BufRead>(r: R) -> Result<Vec<Forecast>, Error> {
+        Ok(Self::from_records(&atcf::read_records(r)?))
+    }
+
+    // from_records groups a-deck records into forecasts. An a-deck writes a
+    // line for each of the wind radii at each lead time, which are combined
+    // into a single point. Each storm's id takes the year of its earliest
+    // record, so forecasts for a storm that lasts past New Year share an id.
+    pub fn from_records(records: &[Record]) -> Vec<Forecast> {
+        let mut years: HashMap<(atcf::Basin, i32), i32> = HashMap::new();
+        for r in records {
+            let year = years.entry((r.basin(), r.number())).or_insert(i32::MAX);
+            *year = (*year).min(r.time().year());
+        }
+
+        let mut forecasts: Vec<Forecast> = vec![];
+        for r in records.iter().filter(|r| r.tau() >= 0) {
+            let location = match r.location() {
+                Some(location) => location.clone(),
+                None => continue,
+            };
+            let i = match forecasts.iter().rposition(|f| {
+                f.storm.basin() == r.basin()
+                    && f.storm.number() == r.number()
+                    && f.technique == r.technique()
+                    && f.init == r.time()
+            }) {
+                Some(i) => i,
+                None => {
+                    let year = years[&(r.basin(), r.number())];
+                    forecasts.push(Forecast {
+                        storm: atcf::Id::new(r.basin(), r.number(), year),
+                        technique: r.technique().to_owned(),
+                        init: r.time(),
+                        points: vec![],
+                    });
+                    forecasts.len() - 1
+                }
+            };
+            let points = &mut forecasts[i].points;
+            if points.iter().all(|p| p.tau != r.tau()) {
+                points.push(ForecastPoint {
+                    tau: r.tau(),
+                    location,
+                    max_wind: r.max_wind(),
+                    min_pressure: r.min_pressure(),
+                });
+            }
+        }
+        for f in &mut forecasts {
+            f.points.sort_by_key(|p| p.tau);
+        }
+        forecasts
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastPoint {
+    tau: i32,
+    location: geo::Location,
+    max_wind: Option<i32>,
+    min_pressure: Option<i32>,
+}
+
+impl ForecastPoint {
+    // tau is the lead time of the point in hours.
+    pub fn tau(&self) -> i32 {
+        self.tau
+    }
+
+    pub fn location(&self) -> &geo::Location {
+        &self.location
+    }
+
+    // max_wind is None for aids that only forecast the track.
+    pub fn max_wind(&self) -> Option<i32> {
+        self.max_wind
+    }
+
+    pub fn min_pressure(&self) -> Option<i32> {
+        self.min_pressure
+    }
+
+    // valid_at returns the time the point forecasts, given the forecast's
+    // initial time.
+    pub fn valid_at(&self, init: DateTime<Utc>) -> DateTime<Utc> {
+        init + Duration::hours(self.tau as i64)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Forecast;
+
+    #[test]
+    fn new_year() {
+        const A_DECK: &str = "\
+AL, 30, 2005123118, 03, OFCL,   0, 260N,  380W,  45, 1000, TS,  34, NEQ,   60,   60,    0,    0,
+AL, 30, 2005123118, 03, OFCL,  12, 255N,  390W,  40,    0, TS,  34, NEQ,   60,   60,    0,    0,
+AL, 30, 2006010100, 03, OFCL,   0, 255N,  390W,  45, 1000, TS,  34, NEQ,   60,   60,    0,    0,
+AL, 30, 2006010100, 03, OFCL,  12, 250N,  400W,  40,    0, TS,  34, NEQ,   60,   60,    0,    0,
+EP, 01, 2006010100, 03, OFCL,   0, 120N, 1400W,  30, 1008, TD,   0,    ,    0,    0,    0,    0,
+";
+        let forecasts = Forecast::read_a_deck(A_DECK.as_bytes()).unwrap();
+        let ids = forecasts
+            .iter()
+            .map(|f| f.storm().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec!["AL302005", "AL302005", "EP012006"]);
+        assert_eq!(forecasts[1].points().len(), 2);
+    }
+}
@@ -1,13 +1,16 @@
-use std::{error::Error, f64::consts::PI, fmt, str::FromStr};
+use std::{f64::consts::PI, fmt, str::FromStr};
 
 use serde::{
     de,
     ser::{self, SerializeSeq},
+    Deserialize, Serialize,
 };
 
+use crate::ValueError;
+
 const R: f64 = 6371e3;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Location {
     lat: f64,
     lng: f64,
@@ -56,6 +59,56 @@ impl Location {
         Distance::from_meters(R * c)
     }
 
+    // intermediate_point returns the point that is the given fraction of the
+    // way from a to b along the great circle that connects them.
+    pub fn intermediate_point(a: &Location, b: &Location, fraction: f64) -> Location {
+        let φ1 = a.lat() * PI / 180.0;
+        let λ1 = a.lng() * PI / 180.0;
+        let φ2 = b.lat() * PI / 180.0;
+        let λ2 = b.lng() * PI / 180.0;
+
+        let δ = Location::distance_between(a, b).in_meters() / R;
+        if δ.abs() < 1e-12 {
+            return a.clone();
+        }
+
+        let s = (fraction * δ).sin() / δ.sin();
+        let r = ((1.0 - fraction) * δ).sin() / δ.sin();
+
+        let x = r * φ1.cos() * λ1.cos() + s * φ2.cos() * λ2.cos();
+        let y = r * φ1.cos() * λ1.sin() + s * φ2.cos() * λ2.sin();
+        let z = r * φ1.sin() + s * φ2.sin();
+
+        Location {
+            lat: z.atan2((x * x + y * y).sqrt()) * 180.0 / PI,
+            lng: y.atan2(x) * 180.0 / PI,
+        }
+    }
+
+    // midpoint returns the point halfway between a and b along the great
+    // circle that connects them.
+    pub fn midpoint(a: &Location, b: &Location) -> Location {
+        Location::intermediate_point(a, b, 0.5)
+    }
+
+    // bearing_to returns the initial bearing of the great circle path from
+    // this location to other, in the range [0, 360).
+    pub fn bearing_to(&self, other: &Location) -> Bearing {
+        let φ1 = self.lat * PI / 180.0;
+        let φ2 = other.lat * PI / 180.0;
+        let δλ = (other.lng - self.lng) * PI / 180.0;
+
+        let y = δλ.sin() * φ2.cos();
+        let x = φ1.cos() * φ2.sin() - φ1.sin() * φ2.cos() * δλ.cos();
+        Bearing::from_radians(y.atan2(x)).normalized()
+    }
+
+    // final_bearing_to returns the bearing on arrival at other when following
+    // the great circle path from this location, in the range [0, 360).
+    pub fn final_bearing_to(&self, other: &Location) -> Bearing {
+        Bearing::from_degrees(other.bearing_to(self).in_degrees() + 180.0).normalized()
+    }
+
     pub fn destination_of(&self, bearing: Bearing, distance: Distance) -> Location {
         let φ1 = self.lat * PI / 180.0;
         let λ1 = self.lng * PI / 180.0;
@@ -76,7 +129,7 @@ impl Location {
 }
 
 impl FromStr for Location {
-    type Err = Box<dyn Error>;
+    type Err = ValueError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let re = regex::Regex::new(
@@ -84,26 +137,28 @@ impl FromStr for Location {
         )
         .unwrap();
 
-        let caps = re.captures(s).ok_or(format!("invalid location: {}", s))?;
+        let invalid = || ValueError::new("location", s);
+        let caps = re.captures(s).ok_or_else(invalid)?;
+        let int = |i: usize| caps.get(i).unwrap().as_str().parse::<i32>();
 
-        let lat_d = caps.get(1).unwrap().as_str().parse::<i32>()?;
-        let lat_m = caps.get(2).unwrap().as_str().parse::<i32>()?;
-        let lat_s = caps.get(3).unwrap().as_str().parse::<i32>()?;
+        let lat_d = int(1).map_err(|_| invalid())?;
+        let lat_m = int(2).map_err(|_| invalid())?;
+        let lat_s = int(3).map_err(|_| invalid())?;
         let lat_v = lat_d as f64 + (lat_m as f64) / 60.0 + (lat_s as f64) / 3600.0;
         let lat_v = match caps.get(4).unwrap().as_str() {
             "N" | "n" => Ok(lat_v),
             "S" | "s" => Ok(-lat_v),
-            _ => Err(format!("invalid location: {}", s)),
+            _ => Err(invalid()),
         }?;
 
-        let lng_d = caps.get(5).unwrap().as_str().parse::<i32>()?;
-        let lng_m = caps.get(6).unwrap().as_str().parse::<i32>()?;
-        let lng_s = caps.get(7).unwrap().as_str().parse::<i32>()?;
+        let lng_d = int(5).map_err(|_| invalid())?;
+        let lng_m = int(6).map_err(|_| invalid())?;
+        let lng_s = int(7).map_err(|_| invalid())?;
         let lng_v = lng_d as f64 + (lng_m as f64) / 60.0 + (lng_s as f64) / 3600.0;
         let lng_v = match caps.get(8).unwrap().as_str() {
             "E" | "e" => Ok(lng_v),
             "W" | "w" => Ok(-lng_v),
-            _ => Err(format!("invalid location: {}", s)),
+            _ => Err(invalid()),
         }?;
 
         Ok(Location {
@@ -226,6 +281,13 @@ impl Bearing {
         Bearing { deg: 270.0 }
     }
 
+    // normalized returns the same bearing in the range [0, 360).
+    pub fn normalized(&self) -> Bearing {
+        Bearing {
+            deg: self.deg.rem_euclid(360.0),
+        }
+    }
+
     pub fn to_dms(&self) -> String {
         let (d, m, s) = to_dms(self.deg);
         format!("{:03}°{:02}′{:02}″", d, m, s)
@@ -233,15 +295,17 @@ impl Bearing {
 }
 
 impl FromStr for Bearing {
-    type Err = Box<dyn Error>;
+    type Err = ValueError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let re = regex::Regex::new(r#"(\d+)°(\d+)[′'](\d+)[″"]"#).unwrap();
 
-        let caps = re.captures(s).ok_or(format!("invalid dms: {}", s))?;
-        let d = caps.get(1).unwrap().as_str().parse::<i32>()?;
-        let m = caps.get(2).unwrap().as_str().parse::<i32>()?;
-        let s = caps.get(3).unwrap().as_str().parse::<i32>()?;
+        let invalid = || ValueError::new("dms", s);
+        let caps = re.captures(s).ok_or_else(invalid)?;
+        let int = |i: usize| caps.get(i).unwrap().as_str().parse::<i32>();
+        let d = int(1).map_err(|_| invalid())?;
+        let m = int(2).map_err(|_| invalid())?;
+        let s = int(3).map_err(|_| invalid())?;
 
         Ok(Bearing {
             deg: d as f64 + m as f64 / 60.0 + s as f64 / 3600.0,
@@ -281,7 +345,141 @@ fn to_dms(v: f64) -> (i32, i32, i32) {
     (d, m, s)
 }
 
-#[derive(Debug, Clone)]
+// Projection maps locations onto the plane of a map image, where x grows to
+// the right and y grows down.
+pub trait Projection {
+    fn location_to_pt(&self, loc: &Location) -> (f64, f64);
+
+    fn pt_to_location(&self, pt: (f64, f64)) -> Location;
+}
+
+// MapProjection is one of the supported projections, as chosen on the command
+// line with --map.projection and recorded in map.json. It is written as the
+// projection's name followed by its parameters as a JSON array:
+//
+//   mercator:[<width>, <height>, <xoff>, <yoff>]
+//   equirectangular:[<width>, <height>, <xoff>, <yoff>]
+//   lcc:[<lat1>, <lat2>, <lat0>, <lng0>, <scale>, <xoff>, <yoff>]
+//   albers:[<lat1>, <lat2>, <lat0>, <lng0>, <scale>, <xoff>, <yoff>]
+//   orthographic:[<lat0>, <lng0>, <scale>, <xoff>, <yoff>]
+//
+// A bare array is taken to be Mercator. The standard parallels of the conic
+// projections may not be symmetric about the equator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum MapProjection {
+    Mercator(Mercator),
+    Equirectangular(Equirectangular),
+    #[serde(rename = "lcc")]
+    LambertConformalConic(LambertConformalConic),
+    #[serde(rename = "albers")]
+    AlbersEqualArea(AlbersEqualArea),
+    Orthographic(Orthographic),
+}
+
+impl MapProjection {
+    pub fn to_str(&self) -> &str {
+        match self {
+            MapProjection::Mercator(_) => "mercator",
+            MapProjection::Equirectangular(_) => "equirectangular",
+            MapProjection::LambertConformalConic(_) => "lcc",
+            MapProjection::AlbersEqualArea(_) => "albers",
+            MapProjection::Orthographic(_) => "orthographic",
+        }
+    }
+
+    fn projection(&self) -> &dyn Projection {
+        match self {
+            MapProjection::Mercator(p) => p,
+            MapProjection::Equirectangular(p) => p,
+            MapProjection::LambertConformalConic(p) => p,
+            MapProjection::AlbersEqualArea(p) => p,
+            MapProjection::Orthographic(p) => p,
+        }
+    }
+
+    fn params(&self) -> Vec<f64> {
+        match self {
+            MapProjection::Mercator(p) => vec![p.width, p.height, p.xoff, p.yoff],
+            MapProjection::Equirectangular(p) => vec![p.width, p.height, p.xoff, p.yoff],
+            MapProjection::LambertConformalConic(p) => p.conic.params(),
+            MapProjection::AlbersEqualArea(p) => p.conic.params(),
+            MapProjection::Orthographic(p) => {
+                vec![p.lat0, p.lng0, p.offset.scale, p.offset.xoff, p.offset.yoff]
+            }
+        }
+    }
+}
+
+impl Projection for MapProjection {
+    fn location_to_pt(&self, loc: &Location) -> (f64, f64) {
+        self.projection().location_to_pt(loc)
+    }
+
+    fn pt_to_location(&self, pt: (f64, f64)) -> Location {
+        self.projection().pt_to_location(pt)
+    }
+}
+
+impl From<Mercator> for MapProjection {
+    fn from(p: Mercator) -> Self {
+        MapProjection::Mercator(p)
+    }
+}
+
+impl fmt::Display for MapProjection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let params = self
+            .params()
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{}:[{}]", self.to_str(), params.join(", "))
+    }
+}
+
+impl FromStr for MapProjection {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ValueError::new("projection", s);
+        let (name, params) = match s.split_once(':') {
+            Some((name, params)) => (name.trim().to_ascii_lowercase(), params),
+            None => ("mercator".to_owned(), s),
+        };
+        let vals: Vec<f64> = serde_json::from_str(params).map_err(|_| invalid())?;
+        match (name.as_str(), &vals[..]) {
+            ("mercator", &[width, height, xoff, yoff]) => {
+                Ok(Mercator::new(width, height, xoff, yoff).into())
+            }
+            ("equirectangular", &[width, height, xoff, yoff]) => Ok(
+                MapProjection::Equirectangular(Equirectangular::new(width, height, xoff, yoff)),
+            ),
+            ("lcc", &[lat1, lat2, lat0, lng0, scale, xoff, yoff]) => {
+                let p = LambertConformalConic::new((lat1, lat2), (lat0, lng0), scale, xoff, yoff);
+                if !is_cone(p.n()) {
+                    return Err(invalid());
+                }
+                Ok(MapProjection::LambertConformalConic(p))
+            }
+            ("albers", &[lat1, lat2, lat0, lng0, scale, xoff, yoff]) => {
+                let p = AlbersEqualArea::new((lat1, lat2), (lat0, lng0), scale, xoff, yoff);
+                if !is_cone(p.n()) {
+                    return Err(invalid());
+                }
+                Ok(MapProjection::AlbersEqualArea(p))
+            }
+            ("orthographic", &[lat0, lng0, scale, xoff, yoff]) => Ok(MapProjection::Orthographic(
+                Orthographic::new((lat0, lng0), scale, xoff, yoff),
+            )),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+// Mercator is the spherical Mercator projection, scaled so that the whole
+// world is width wide and height high.
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Mercator {
     width: f64,
     height: f64,
@@ -314,8 +512,10 @@ impl Mercator {
     pub fn yoff(&self) -> f64 {
         self.yoff
     }
+}
 
-    pub fn location_to_pt(&self, loc: &Location) -> (f64, f64) {
+impl Projection for Mercator {
+    fn location_to_pt(&self, loc: &Location) -> (f64, f64) {
         let lat = loc.lat();
         let lng = loc.lng();
         let lat_rads = lat * (PI / 180.0);
@@ -326,7 +526,7 @@ impl Mercator {
         )
     }
 
-    pub fn pt_to_location(&self, pt: (f64, f64)) -> Location {
+    fn pt_to_location(&self, pt: (f64, f64)) -> Location {
         let (x, y) = pt;
         let a = (-2.0 * PI * (y - self.height / 2.0 - self.yoff)) / self.height;
         let lat_deg = 2.0 * a.exp().atan() - PI / 2.0;
@@ -348,12 +548,12 @@ impl std::fmt::Display for Mercator {
 }
 
 impl std::str::FromStr for Mercator {
-    type Err = Box<dyn Error>;
+    type Err = ValueError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let vals: Vec<f64> = serde_json::from_str(s)?;
+        let vals: Vec<f64> = serde_json::from_str(s).map_err(|_| ValueError::new("mercator", s))?;
         if vals.len() != 4 {
-            return Err(format!("invalid mercator: {}", s).into());
+            return Err(ValueError::new("mercator", s));
         }
         Ok(Mercator {
             width: vals[0],
@@ -363,3 +563,386 @@ impl std::str::FromStr for Mercator {
         })
     }
 }
+
+// Equirectangular is the plate carrée projection, in which latitude and
+// longitude map linearly to y and x. The whole world is width wide and
+// height high.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Equirectangular {
+    width: f64,
+    height: f64,
+    xoff: f64,
+    yoff: f64,
+}
+
+impl Equirectangular {
+    pub fn new(width: f64, height: f64, xoff: f64, yoff: f64) -> Equirectangular {
+        Equirectangular {
+            width,
+            height,
+            xoff,
+            yoff,
+        }
+    }
+}
+
+impl Projection for Equirectangular {
+    fn location_to_pt(&self, loc: &Location) -> (f64, f64) {
+        (
+            self.width / 2.0 + loc.lng() * self.width / 360.0 + self.xoff,
+            self.height / 2.0 - loc.lat() * self.height / 180.0 + self.yoff,
+        )
+    }
+
+    fn pt_to_location(&self, pt: (f64, f64)) -> Location {
+        let (x, y) = pt;
+        Location::new(
+            (self.height / 2.0 + self.yoff - y) * 180.0 / self.height,
+            (x - self.xoff - self.width / 2.0) * 360.0 / self.width,
+        )
+    }
+}
+
+// Offset places projected coordinates, which are in units of the earth's
+// radius, onto the map. The origin is moved to (xoff, yoff) and y is flipped
+// to grow down.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Offset {
+    scale: f64,
+    xoff: f64,
+    yoff: f64,
+}
+
+impl Offset {
+    fn plane_to_pt(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        (self.xoff + x * self.scale, self.yoff - y * self.scale)
+    }
+
+    fn pt_to_plane(&self, (x, y): (f64, f64)) -> (f64, f64) {
+        ((x - self.xoff) / self.scale, (self.yoff - y) / self.scale)
+    }
+}
+
+// Conic holds the parameters shared by the conic projections: the standard
+// parallels, where the cone touches the globe, and the origin, which is
+// projected to (xoff, yoff), in degrees.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Conic {
+    lat1: f64,
+    lat2: f64,
+    lat0: f64,
+    lng0: f64,
+    #[serde(flatten)]
+    offset: Offset,
+}
+
+impl Conic {
+    fn new(
+        (lat1, lat2): (f64, f64),
+        (lat0, lng0): (f64, f64),
+        scale: f64,
+        xoff: f64,
+        yoff: f64,
+    ) -> Conic {
+        Conic {
+            lat1,
+            lat2,
+            lat0,
+            lng0,
+            offset: Offset { scale, xoff, yoff },
+        }
+    }
+
+    fn params(&self) -> Vec<f64> {
+        vec![
+            self.lat1,
+            self.lat2,
+            self.lat0,
+            self.lng0,
+            self.offset.scale,
+            self.offset.xoff,
+            self.offset.yoff,
+        ]
+    }
+
+    // polar_to_pt places the point at polar coordinates (rho, theta) around
+    // the cone's apex, where rho0 is the radius of the origin.
+    fn polar_to_pt(&self, rho: f64, rho0: f64, theta: f64) -> (f64, f64) {
+        self.offset
+            .plane_to_pt((rho * theta.sin(), rho0 - rho * theta.cos()))
+    }
+
+    // pt_to_polar returns the polar coordinates (rho, theta) of pt around the
+    // cone's apex, for a cone with constant n.
+    fn pt_to_polar(&self, pt: (f64, f64), rho0: f64, n: f64) -> (f64, f64) {
+        let (x, y) = self.offset.pt_to_plane(pt);
+        let (x, dy) = (x * n.signum(), (rho0 - y) * n.signum());
+        (n.signum() * x.hypot(dy), x.atan2(dy))
+    }
+}
+
+// LambertConformalConic is a conformal conic projection with two standard
+// parallels. Angles are preserved, which keeps the shapes of mid-latitude
+// tracks true.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LambertConformalConic {
+    #[serde(flatten)]
+    conic: Conic,
+}
+
+impl LambertConformalConic {
+    // new creates a projection with the standard parallels (lat1, lat2) and
+    // the origin (lat0, lng0), in degrees. The earth's radius is scale
+    // pixels and the origin is placed at (xoff, yoff).
+    pub fn new(
+        parallels: (f64, f64),
+        origin: (f64, f64),
+        scale: f64,
+        xoff: f64,
+        yoff: f64,
+    ) -> LambertConformalConic {
+        LambertConformalConic {
+            conic: Conic::new(parallels, origin, scale, xoff, yoff),
+        }
+    }
+
+    fn n(&self) -> f64 {
+        let (lat1, lat2) = (self.conic.lat1.to_radians(), self.conic.lat2.to_radians());
+        if (lat1 - lat2).abs() < 1e-10 {
+            lat1.sin()
+        } else {
+            (lat1.cos() / lat2.cos()).ln() / (tan_half(lat2) / tan_half(lat1)).ln()
+        }
+    }
+
+    fn rho(&self, lat: f64, n: f64) -> f64 {
+        let lat1 = self.conic.lat1.to_radians();
+        lat1.cos() * tan_half(lat1).powf(n) / n / tan_half(lat).powf(n)
+    }
+}
+
+impl Projection for LambertConformalConic {
+    fn location_to_pt(&self, loc: &Location) -> (f64, f64) {
+        let n = self.n();
+        let theta = n * wrap_radians((loc.lng() - self.conic.lng0).to_radians());
+        self.conic.polar_to_pt(
+            self.rho(loc.lat().to_radians(), n),
+            self.rho(self.conic.lat0.to_radians(), n),
+            theta,
+        )
+    }
+
+    fn pt_to_location(&self, pt: (f64, f64)) -> Location {
+        let n = self.n();
+        let (rho, theta) = self
+            .conic
+            .pt_to_polar(pt, self.rho(self.conic.lat0.to_radians(), n), n);
+        let f = self.rho(0.0, n);
+        let lat = 2.0 * (f / rho).powf(1.0 / n).atan() - PI / 2.0;
+        Location::new(
+            lat.to_degrees(),
+            wrap_radians(self.conic.lng0.to_radians() + theta / n).to_degrees(),
+        )
+    }
+}
+
+// AlbersEqualArea is an equal-area conic projection with two standard
+// parallels. Regions of equal area on the globe have equal area on the map,
+// so binned statistics can be compared across latitudes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlbersEqualArea {
+    #[serde(flatten)]
+    conic: Conic,
+}
+
+impl AlbersEqualArea {
+    // new creates a projection with the standard parallels (lat1, lat2) and
+    // the origin (lat0, lng0), in degrees. The earth's radius is scale
+    // pixels and the origin is placed at (xoff, yoff).
+    pub fn new(
+        parallels: (f64, f64),
+        origin: (f64, f64),
+        scale: f64,
+        xoff: f64,
+        yoff: f64,
+    ) -> AlbersEqualArea {
+        AlbersEqualArea {
+            conic: Conic::new(parallels, origin, scale, xoff, yoff),
+        }
+    }
+
+    fn n(&self) -> f64 {
+        (self.conic.lat1.to_radians().sin() + self.conic.lat2.to_radians().sin()) / 2.0
+    }
+
+    fn c(&self, n: f64) -> f64 {
+        let lat1 = self.conic.lat1.to_radians();
+        lat1.cos().powi(2) + 2.0 * n * lat1.sin()
+    }
+
+    fn rho(&self, lat: f64, n: f64) -> f64 {
+        (self.c(n) - 2.0 * n * lat.sin()).sqrt() / n
+    }
+}
+
+impl Projection for AlbersEqualArea {
+    fn location_to_pt(&self, loc: &Location) -> (f64, f64) {
+        let n = self.n();
+        let theta = n * wrap_radians((loc.lng() - self.conic.lng0).to_radians());
+        self.conic.polar_to_pt(
+            self.rho(loc.lat().to_radians(), n),
+            self.rho(self.conic.lat0.to_radians(), n),
+            theta,
+        )
+    }
+
+    fn pt_to_location(&self, pt: (f64, f64)) -> Location {
+        let n = self.n();
+        let (rho, theta) = self
+            .conic
+            .pt_to_polar(pt, self.rho(self.conic.lat0.to_radians(), n), n);
+        let lat = ((self.c(n) - (rho * n).powi(2)) / (2.0 * n))
+            .clamp(-1.0, 1.0)
+            .asin();
+        Location::new(
+            lat.to_degrees(),
+            wrap_radians(self.conic.lng0.to_radians() + theta / n).to_degrees(),
+        )
+    }
+}
+
+// Orthographic shows the globe as seen from space above (lat0, lng0). Only
+// the hemisphere facing the viewer is visible: locations on the far side are
+// projected onto the near side and points beyond the edge of the globe have
+// no location, so pt_to_location returns NaN for them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Orthographic {
+    lat0: f64,
+    lng0: f64,
+    #[serde(flatten)]
+    offset: Offset,
+}
+
+impl Orthographic {
+    // new creates a projection centered on (lat0, lng0), in degrees. The
+    // earth's radius is scale pixels and the center is placed at
+    // (xoff, yoff).
+    pub fn new((lat0, lng0): (f64, f64), scale: f64, xoff: f64, yoff: f64) -> Orthographic {
+        Orthographic {
+            lat0,
+            lng0,
+            offset: Offset { scale, xoff, yoff },
+        }
+    }
+}
+
+impl Projection for Orthographic {
+    fn location_to_pt(&self, loc: &Location) -> (f64, f64) {
+        let lat0 = self.lat0.to_radians();
+        let (lat, dlng) = (loc.lat().to_radians(), (loc.lng() - self.lng0).to_radians());
+        self.offset.plane_to_pt((
+            lat.cos() * dlng.sin(),
+            lat0.cos() * lat.sin() - lat0.sin() * lat.cos() * dlng.cos(),
+        ))
+    }
+
+    fn pt_to_location(&self, pt: (f64, f64)) -> Location {
+        let (x, y) = self.offset.pt_to_plane(pt);
+        let rho = x.hypot(y);
+        if rho == 0.0 {
+            return Location::new(self.lat0, self.lng0);
+        }
+        let (lat0, c) = (self.lat0.to_radians(), rho.asin());
+        let lat = (c.cos() * lat0.sin() + y * c.sin() * lat0.cos() / rho).asin();
+        let lng = self.lng0.to_radians()
+            + (x * c.sin()).atan2(rho * c.cos() * lat0.cos() - y * c.sin() * lat0.sin());
+        Location::new(lat.to_degrees(), wrap_radians(lng).to_degrees())
+    }
+}
+
+// is_cone reports whether n, the cone constant of a conic projection, gives
+// a cone. It is 0 when the standard parallels are symmetric about the
+// equator, which would flatten the cone into a cylinder.
+fn is_cone(n: f64) -> bool {
+    n.is_finite() && n.abs() > 1e-10
+}
+
+fn tan_half(lat: f64) -> f64 {
+    (PI / 4.0 + lat / 2.0).tan()
+}
+
+// wrap_radians normalizes an angle to [-PI, PI).
+fn wrap_radians(a: f64) -> f64 {
+    (a + PI).rem_euclid(2.0 * PI) - PI
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Location, MapProjection, Projection};
+
+    #[test]
+    fn projections() {
+        let projections = [
+            "[10368.6, 10310.9, -2160.1, -3566.7]",
+            "equirectangular:[3600, 1800, 0, 0]",
+            "lcc:[20, 50, 30, -60, 1000, 500, 500]",
+            "albers:[20, 50, 30, -60, 1000, 500, 500]",
+            "orthographic:[30, -60, 1000, 500, 500]",
+        ];
+        let locations = [(25.8, -80.2), (10.0, -20.0), (45.0, -100.5), (60.0, -30.0)];
+        for s in projections {
+            let projection = s.parse::<MapProjection>().unwrap();
+            let again = projection.to_string().parse::<MapProjection>().unwrap();
+            assert_eq!(projection.to_str(), again.to_str());
+            for (lat, lng) in locations {
+                let pt = projection.location_to_pt(&Location::new(lat, lng));
+                assert_eq!(pt, again.location_to_pt(&Location::new(lat, lng)));
+                let loc = projection.pt_to_location(pt);
+                assert!(
+                    (loc.lat() - lat).abs() < 1e-9 && (loc.lng() - lng).abs() < 1e-9,
+                    "{}: ({}, {}) => {:?} => {:?}",
+                    s,
+                    lat,
+                    lng,
+                    pt,
+                    loc
+                );
+            }
+        }
+
+        let origin = "lcc:[20, 50, 30, -60, 1000, 500, 500]"
+            .parse::<MapProjection>()
+            .unwrap()
+            .location_to_pt(&Location::new(30.0, -60.0));
+        assert!((origin.0 - 500.0).abs() < 1e-9 && (origin.1 - 500.0).abs() < 1e-9);
+
+        let albers = "albers:[20, 50, 30, -60, 1000, 500, 500]"
+            .parse::<MapProjection>()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&albers).unwrap(),
+            serde_json::json!({
+                "type": "albers",
+                "lat1": 20.0,
+                "lat2": 50.0,
+                "lat0": 30.0,
+                "lng0": -60.0,
+                "scale": 1000.0,
+                "xoff": 500.0,
+                "yoff": 500.0,
+            })
+        );
+
+        assert!("lcc:[20, 50]".parse::<MapProjection>().is_err());
+        assert!("lcc:[-30, 30, 0, -60, 1000, 500, 500]"
+            .parse::<MapProjection>()
+            .is_err());
+        assert!("lcc:[0, 0, 0, -60, 1000, 500, 500]"
+            .parse::<MapProjection>()
+            .is_err());
+        assert!("albers:[-30, 30, 0, -60, 1000, 500, 500]"
+            .parse::<MapProjection>()
+            .is_err());
+        assert!("gnomonic:[0, 0, 1, 0, 0]".parse::<MapProjection>().is_err());
+    }
+}
@@ -1,14 +1,22 @@
-use std::{error::Error, str::FromStr};
+use std::{
+    collections::BTreeMap,
+    error::Error,
+    fmt,
+    num::ParseIntError,
+    pin::Pin,
+    str::FromStr,
+    task::{Context, Poll},
+};
 
 use chrono::prelude::*;
-use chrono::{DateTime, NaiveDate, Utc};
-use csv_async::{StringRecord, StringRecordsStream};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use csv_async::{AsyncReaderBuilder, StringRecordsIntoStream, StringRecordsStream};
 use serde::{de, ser};
 use serde::{Deserialize, Serialize};
 use tokio::io;
-use tokio_stream::StreamExt;
+use tokio_stream::{Stream, StreamExt};
 
-use crate::{atcf, geo};
+use crate::{atcf, geo, swath, ValueError};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Storm {
@@ -18,6 +26,12 @@ pub struct Storm {
 }
 
 impl Storm {
+    // new creates a storm from a track that was read from something other
+    // than HURDAT2. The track must be ordered by time.
+    pub fn new(id: atcf::Id, name: Option<String>, track: Vec<TrackEntry>) -> Storm {
+        Storm { id, name, track }
+    }
+
     pub fn track(&self) -> &[TrackEntry] {
         &self.track
     }
@@ -30,75 +44,952 @@ impl Storm {
         &self.id
     }
 
+    pub fn metrics(&self) -> Metrics {
+        Metrics::from_track(&self.track)
+    }
+
+    // motion returns the storm's forward speed and heading over each segment
+    // of its track.
+    pub fn motion(&self) -> Vec<Motion> {
+        Motion::from_track(&self.track)
+    }
+
+    pub fn peak_category(&self) -> Option<Category> {
+        self.track.iter().map(|e| e.category()).max()
+    }
+
+    // basins returns the basins the storm's track passed through, in the
+    // order it entered them.
+    pub fn basins(&self) -> Vec<atcf::Basin> {
+        let mut basins = vec![];
+        for basin in self.track.iter().filter_map(|e| e.basin()) {
+            if !basins.contains(&basin) {
+                basins.push(basin);
+            }
+        }
+        basins
+    }
+
+    // crosses_basins reports whether the storm was in a basin other than the
+    // one it was numbered in, such as an Atlantic storm that crossed Central
+    // America into the eastern Pacific.
+    pub fn crosses_basins(&self) -> bool {
+        self.basins().iter().any(|b| *b != self.id.basin())
+    }
+
+    // ace returns the storm's Accumulated Cyclone Energy in units of
+    // 10^4 kt^2.
+    pub fn ace(&self) -> f64 {
+        self.track.iter().map(|e| e.ace()).sum()
+    }
+
+    // position_at returns the storm's position and intensity at time t,
+    // interpolated between the surrounding track entries. The location moves
+    // along the great circle between entries while wind and pressure change
+    // linearly. It returns None if t falls outside of the track.
+    pub fn position_at(&self, t: DateTime<Utc>) -> Option<TrackPoint> {
+        let i = self.track.partition_point(|e| e.time < t);
+        let b = self.track.get(i)?;
+        if b.time == t {
+            return Some(TrackPoint::from(b));
+        }
+        let a = &self.track[i.checked_sub(1)?];
+        Some(TrackPoint::between(a, b, t))
+    }
+
+    // resample returns the storm's track at uniformly spaced times. Samples
+    // fall on multiples of interval (measured from the Unix epoch), so that
+    // the tracks of different storms line up.
+    pub fn resample(&self, interval: Duration) -> Vec<TrackPoint> {
+        let step = interval.num_seconds();
+        let (first, last) = match (self.track.first(), self.track.last()) {
+            (Some(first), Some(last)) if step > 0 => (first, last),
+            _ => return vec![],
+        };
+        let start = first.time.timestamp();
+        let start = start + (step - start.rem_euclid(step)) % step;
+        (start..=last.time.timestamp())
+            .step_by(step as usize)
+            .filter_map(|s| Utc.timestamp_opt(s, 0).single())
+            .filter_map(|t| self.position_at(t))
+            .collect()
+    }
+
+    // wind_swath returns the area exposed to winds of at least kts (34, 50 or
+    // 64) over the storm's lifetime. The wind radii are interpolated along the
+    // track at the given interval. See swath::Swath.
+    pub fn wind_swath(&self, kts: i32, interval: Duration) -> swath::Swath {
+        swath::Swath::from_storm(self, kts, interval)
+    }
+
     pub async fn from_record_stream<'a, R>(
         stream: &mut StringRecordsStream<'a, R>,
-    ) -> Option<Result<Storm, Box<dyn Error>>>
+    ) -> Option<Result<Storm, crate::Error>>
     where
         R: io::AsyncRead + Unpin + std::marker::Send,
     {
-        Some(
-            stream
-                .next()
-                .await
-                .map(|r| Storm::from_record(r, stream))?
-                .await,
-        )
+        let mut builder = StormBuilder::new(ParseMode::Strict);
+        while let Some(record) = stream.next().await {
+            match record.map_err(|e| e.into()).and_then(|r| builder.push(&r)) {
+                Ok(Some(storm)) => return Some(Ok(storm)),
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+        builder.finish().transpose()
     }
+}
 
-    async fn from_record<'a, R>(
-        record: Result<StringRecord, csv_async::Error>,
-        stream: &mut StringRecordsStream<'a, R>,
-    ) -> Result<Storm, Box<dyn Error>>
-    where
-        R: io::AsyncRead + Unpin + std::marker::Send,
-    {
-        let header = Header::from_record(&record?)?;
-        let track_entries = stream
-            .take(header.num_track_entries)
-            .map(|r| TrackEntry::from_record(&r?))
-            .collect::<Result<Vec<_>, _>>()
-            .await?;
-        Ok(Storm {
-            id: header.id,
-            name: header.name,
-            track: track_entries,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMode {
+    // Strict fails on the first invalid record.
+    Strict,
+
+    // Lenient repairs or skips invalid records, reporting each one as a
+    // Diagnostic, and keeps going.
+    Lenient,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParseError {
+    line: u64,
+    storm: Option<atcf::Id>,
+    column: Option<usize>,
+    field: &'static str,
+    value: String,
+    message: String,
+}
+
+impl ParseError {
+    fn new<T: Record>(
+        record: &T,
+        column: Option<usize>,
+        field: &'static str,
+        value: &str,
+        message: String,
+    ) -> ParseError {
+        ParseError {
+            line: record.line(),
+            storm: None,
+            column,
+            field,
+            value: value.to_owned(),
+            message,
+        }
+    }
+
+    fn with_storm(mut self, id: &atcf::Id) -> ParseError {
+        self.storm = Some(id.clone());
+        self
+    }
+
+    pub fn line(&self) -> u64 {
+        self.line
+    }
+
+    pub fn storm(&self) -> Option<&atcf::Id> {
+        self.storm.as_ref()
+    }
+
+    pub fn column(&self) -> Option<usize> {
+        self.column
+    }
+
+    pub fn field(&self) -> &str {
+        self.field
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "line {}", self.line)?;
+        if let Some(storm) = &self.storm {
+            write!(f, " ({})", storm)?;
+        }
+        if let Some(column) = self.column {
+            write!(f, ", column {}", column)?;
+        }
+        write!(f, ": {}: {} {:?}", self.message, self.field, self.value)
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    // Repaired indicates the offending value was replaced and the record kept.
+    Repaired,
+
+    // SkippedEntry indicates the track entry was dropped from its storm.
+    SkippedEntry,
+
+    // SkippedStorm indicates the header and all of its track entries were
+    // dropped.
+    SkippedStorm,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    error: ParseError,
+    resolution: Resolution,
+}
+
+impl Diagnostic {
+    pub fn error(&self) -> &ParseError {
+        &self.error
+    }
+
+    pub fn resolution(&self) -> Resolution {
+        self.resolution
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let action = match self.resolution {
+            Resolution::Repaired => "repaired",
+            Resolution::SkippedEntry => "skipped entry",
+            Resolution::SkippedStorm => "skipped storm",
+        };
+        write!(f, "{} [{}]", self.error, action)
+    }
+}
+
+// Record is the subset of a CSV record that the HURDAT2 parsers need. It
+// allows the same parsing logic to be used by both the blocking reader and
+// the async stream.
+trait Record {
+    fn get(&self, i: usize) -> Option<&str>;
+
+    fn len(&self) -> usize;
+
+    fn line(&self) -> u64;
+}
+
+impl Record for csv::StringRecord {
+    fn get(&self, i: usize) -> Option<&str> {
+        csv::StringRecord::get(self, i)
+    }
+
+    fn len(&self) -> usize {
+        csv::StringRecord::len(self)
+    }
+
+    fn line(&self) -> u64 {
+        self.position().map_or(0, |p| p.line())
+    }
+}
+
+impl Record for csv_async::StringRecord {
+    fn get(&self, i: usize) -> Option<&str> {
+        csv_async::StringRecord::get(self, i)
+    }
+
+    fn len(&self) -> usize {
+        csv_async::StringRecord::len(self)
+    }
+
+    fn line(&self) -> u64 {
+        self.position().map_or(0, |p| p.line())
+    }
+}
+
+fn get_field<'a, T: Record>(
+    record: &'a T,
+    column: usize,
+    field: &'static str,
+) -> Result<&'a str, ParseError> {
+    record
+        .get(column)
+        .map(str::trim)
+        .ok_or_else(|| ParseError::new(record, Some(column), field, "", "missing value".into()))
+}
+
+fn parse_field<T, V, E, F>(
+    record: &T,
+    column: usize,
+    field: &'static str,
+    f: F,
+) -> Result<V, ParseError>
+where
+    T: Record,
+    E: fmt::Display,
+    F: FnOnce(&str) -> Result<V, E>,
+{
+    let value = get_field(record, column, field)?;
+    f(value).map_err(|e| ParseError::new(record, Some(column), field, value, e.to_string()))
+}
+
+// repair replaces the value of a field that failed to parse with its default
+// when parsing leniently. The error is recorded in repairs.
+fn repair<V: Default>(
+    res: Result<V, ParseError>,
+    mode: ParseMode,
+    repairs: &mut Vec<ParseError>,
+) -> Result<V, ParseError> {
+    match (res, mode) {
+        (Err(e), ParseMode::Lenient) => {
+            repairs.push(e);
+            Ok(V::default())
+        }
+        (res, _) => res,
+    }
+}
+
+// StormBuilder accumulates a header record and the track records that
+// follow it, producing a Storm once all of the track entries have been seen.
+//
+// In strict mode, the number of track entries listed in the header decides
+// where a storm ends. In lenient mode, storms end at the next header record
+// so that a bad count or a skipped entry can't misalign the rest of the file.
+struct StormBuilder {
+    mode: ParseMode,
+    header: Option<Header>,
+    track: Vec<TrackEntry>,
+    skipping: bool,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl StormBuilder {
+    fn new(mode: ParseMode) -> Self {
+        Self {
+            mode,
+            header: None,
+            track: Vec::new(),
+            skipping: false,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    fn push<T: Record>(&mut self, record: &T) -> Result<Option<Storm>, crate::Error> {
+        match self.mode {
+            ParseMode::Strict => Ok(self.push_strict(record)?),
+            ParseMode::Lenient => Ok(self.push_lenient(record)),
+        }
+    }
+
+    fn finish(&mut self) -> Result<Option<Storm>, crate::Error> {
+        match self.mode {
+            ParseMode::Strict => match self.header.take() {
+                Some(h) => Err(crate::Error::Parse(ParseError {
+                    line: h.line,
+                    storm: Some(h.id),
+                    column: Some(2),
+                    field: "num_track_entries",
+                    value: h.num_track_entries.unwrap_or_default().to_string(),
+                    message: format!("found only {} track entries", self.track.len()),
+                })),
+                None => Ok(None),
+            },
+            ParseMode::Lenient => Ok(self.take_checked()),
+        }
+    }
+
+    fn push_strict<T: Record>(&mut self, record: &T) -> Result<Option<Storm>, ParseError> {
+        let mut repairs = vec![];
+        let res = match &self.header {
+            None => {
+                Header::from_record(record, self.mode, &mut repairs).map(|h| self.header = Some(h))
+            }
+            Some(h) => TrackEntry::from_record(record, self.mode, &mut repairs)
+                .map(|e| self.track.push(e))
+                .map_err(|e| e.with_storm(&h.id)),
+        };
+        if let Err(e) = res {
+            self.header = None;
+            self.track.clear();
+            return Err(e);
+        }
+
+        match &self.header {
+            Some(h) if h.num_track_entries == Some(self.track.len()) => Ok(self.take()),
+            _ => Ok(None),
+        }
+    }
+
+    fn push_lenient<T: Record>(&mut self, record: &T) -> Option<Storm> {
+        if Header::is_header(record) {
+            let storm = self.take_checked();
+            let mut repairs = vec![];
+            match Header::from_record(record, self.mode, &mut repairs) {
+                Ok(h) => {
+                    for e in repairs {
+                        self.report(e.with_storm(&h.id), Resolution::Repaired);
+                    }
+                    self.header = Some(h);
+                    self.skipping = false;
+                }
+                Err(e) => {
+                    self.report(e, Resolution::SkippedStorm);
+                    self.skipping = true;
+                }
+            }
+            return storm;
+        }
+
+        let id = match &self.header {
+            Some(h) => h.id.clone(),
+            None => {
+                if !self.skipping {
+                    let e = ParseError::new(
+                        record,
+                        None,
+                        "record",
+                        record.get(0).unwrap_or_default(),
+                        "track entry does not follow a storm header".into(),
+                    );
+                    self.report(e, Resolution::SkippedEntry);
+                }
+                return None;
+            }
+        };
+
+        let mut repairs = vec![];
+        match TrackEntry::from_record(record, self.mode, &mut repairs) {
+            Ok(entry) => {
+                for e in repairs {
+                    self.report(e.with_storm(&id), Resolution::Repaired);
+                }
+                self.track.push(entry);
+            }
+            Err(e) => self.report(e.with_storm(&id), Resolution::SkippedEntry),
+        }
+        None
+    }
+
+    fn report(&mut self, error: ParseError, resolution: Resolution) {
+        self.diagnostics.push(Diagnostic { error, resolution });
+    }
+
+    fn take(&mut self) -> Option<Storm> {
+        self.header.take().map(|h| Storm {
+            id: h.id,
+            name: h.name,
+            track: std::mem::take(&mut self.track),
         })
     }
+
+    // take_checked takes the current storm, reporting a repair if the
+    // number of track entries doesn't agree with the header.
+    fn take_checked(&mut self) -> Option<Storm> {
+        if let Some(h) = &self.header {
+            match h.num_track_entries {
+                Some(n) if n != self.track.len() => {
+                    let e = ParseError {
+                        line: h.line,
+                        storm: Some(h.id.clone()),
+                        column: Some(2),
+                        field: "num_track_entries",
+                        value: n.to_string(),
+                        message: format!("found {} valid track entries", self.track.len()),
+                    };
+                    self.report(e, Resolution::Repaired);
+                }
+                _ => {}
+            }
+        }
+        self.take()
+    }
+}
+
+// TrackPoint is a storm's position and intensity at an instant, which may
+// fall between the entries of its best track.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrackPoint {
+    time: DateTime<Utc>,
+    location: geo::Location,
+    max_sustained_wind: f64,
+    min_pressure: Option<f64>,
 }
 
-fn parse_location(lat: &str, lng: &str) -> Result<geo::Location, Box<dyn Error>> {
-    if lat.is_empty() {
-        return Err("empty latitude".into());
+impl TrackPoint {
+    pub fn time(&self) -> DateTime<Utc> {
+        self.time
     }
 
-    if lng.is_empty() {
-        return Err("empty longitude".into());
+    pub fn location(&self) -> &geo::Location {
+        &self.location
     }
 
-    let v = lat[..lat.len() - 1].parse::<f64>()?;
-    let lat = match lat.chars().last().unwrap() {
-        'N' | 'n' => v,
-        'S' | 's' => -v,
-        _ => return Err(format!("invalid latitude: {}", lat).into()),
+    pub fn max_sustained_wind(&self) -> f64 {
+        self.max_sustained_wind
+    }
+
+    pub fn min_pressure(&self) -> Option<f64> {
+        self.min_pressure
+    }
+
+    fn between(a: &TrackEntry, b: &TrackEntry, t: DateTime<Utc>) -> TrackPoint {
+        let span = (b.time - a.time).num_seconds() as f64;
+        let f = if span > 0.0 {
+            (t - a.time).num_seconds() as f64 / span
+        } else {
+            0.0
+        };
+        let lerp = |x: f64, y: f64| x + (y - x) * f;
+        TrackPoint {
+            time: t,
+            location: geo::Location::intermediate_point(&a.location, &b.location, f),
+            max_sustained_wind: lerp(a.max_sustained_wind as f64, b.max_sustained_wind as f64),
+            min_pressure: match (a.min_pressure, b.min_pressure) {
+                (Some(x), Some(y)) => Some(lerp(x as f64, y as f64)),
+                _ => None,
+            },
+        }
+    }
+}
+
+impl From<&TrackEntry> for TrackPoint {
+    fn from(e: &TrackEntry) -> TrackPoint {
+        TrackPoint {
+            time: e.time,
+            location: e.location.clone(),
+            max_sustained_wind: e.max_sustained_wind as f64,
+            min_pressure: e.min_pressure.map(|p| p as f64),
+        }
+    }
+}
+
+// Hurdat2Reader is a blocking reader of HURDAT2 data that yields each storm
+// in turn.
+pub struct Hurdat2Reader<R: std::io::Read> {
+    reader: csv::Reader<R>,
+    record: csv::StringRecord,
+    builder: StormBuilder,
+    done: bool,
+}
+
+impl<R: std::io::Read> Hurdat2Reader<R> {
+    pub fn new(r: R, mode: ParseMode) -> Self {
+        Self {
+            reader: csv::ReaderBuilder::new()
+                .flexible(true)
+                .has_headers(false)
+                .from_reader(r),
+            record: csv::StringRecord::new(),
+            builder: StormBuilder::new(mode),
+            done: false,
+        }
+    }
+
+    // diagnostics returns the problems that have been repaired or skipped so
+    // far. It is always empty in strict mode.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.builder.diagnostics
+    }
+}
+
+impl<R: std::io::Read> Iterator for Hurdat2Reader<R> {
+    type Item = Result<Storm, crate::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            match self.reader.read_record(&mut self.record) {
+                Ok(true) => match self.builder.push(&self.record) {
+                    Ok(Some(storm)) => return Some(Ok(storm)),
+                    Ok(None) => continue,
+                    Err(e) => return Some(Err(e)),
+                },
+                Ok(false) => {
+                    self.done = true;
+                    return self.builder.finish().transpose();
+                }
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+        None
+    }
+}
+
+// Hurdat2Stream adapts a stream of CSV records into a stream of storms.
+pub struct Hurdat2Stream<S> {
+    records: S,
+    builder: StormBuilder,
+    done: bool,
+}
+
+impl<S> Hurdat2Stream<S>
+where
+    S: Stream<Item = Result<csv_async::StringRecord, csv_async::Error>> + Unpin,
+{
+    pub fn new(records: S, mode: ParseMode) -> Self {
+        Self {
+            records,
+            builder: StormBuilder::new(mode),
+            done: false,
+        }
+    }
+
+    // diagnostics returns the problems that have been repaired or skipped so
+    // far. It is always empty in strict mode.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.builder.diagnostics
+    }
+}
+
+impl<'a, R> Hurdat2Stream<StringRecordsIntoStream<'a, R>>
+where
+    R: io::AsyncRead + Unpin + std::marker::Send + 'a,
+{
+    pub fn from_reader(r: R, mode: ParseMode) -> Self {
+        Self::new(
+            AsyncReaderBuilder::new()
+                .flexible(true)
+                .has_headers(false)
+                .create_reader(r)
+                .into_records(),
+            mode,
+        )
+    }
+}
+
+impl<S> Stream for Hurdat2Stream<S>
+where
+    S: Stream<Item = Result<csv_async::StringRecord, csv_async::Error>> + Unpin,
+{
+    type Item = Result<Storm, crate::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        while !this.done {
+            match Pin::new(&mut this.records).poll_next(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(Some(Ok(record))) => match this.builder.push(&record) {
+                    Ok(Some(storm)) => return Poll::Ready(Some(Ok(storm))),
+                    Ok(None) => continue,
+                    Err(e) => return Poll::Ready(Some(Err(e))),
+                },
+                Poll::Ready(Some(Err(e))) => return Poll::Ready(Some(Err(e.into()))),
+                Poll::Ready(None) => {
+                    this.done = true;
+                    return Poll::Ready(this.builder.finish().transpose());
+                }
+            }
+        }
+        Poll::Ready(None)
+    }
+}
+
+// Hurdat2Writer writes storms in the fixed-column HURDAT2 format published
+// by the NHC. Missing values are written as -999, so parsing a published file
+// and writing its storms back out reproduces the original bytes.
+pub struct Hurdat2Writer<W: std::io::Write> {
+    w: W,
+}
+
+impl<W: std::io::Write> Hurdat2Writer<W> {
+    pub fn new(w: W) -> Self {
+        Self { w }
+    }
+
+    pub fn write(&mut self, storm: &Storm) -> std::io::Result<()> {
+        writeln!(
+            self.w,
+            "{},{:>19},{:>7},",
+            storm.id,
+            storm.name.as_deref().unwrap_or("UNNAMED"),
+            storm.track.len()
+        )?;
+        for entry in &storm.track {
+            entry.write_hurdat2(&mut self.w)?;
+        }
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.w.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.w
+    }
+}
+
+// Storms also have a compact binary encoding, which is used to cache parsed
+// datasets (see cache). Integers are little endian and missing values are
+// written as MISSING.
+const MISSING: i16 = i16::MIN;
+
+impl Storm {
+    pub(crate) fn write_binary<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        write_str(w, &self.id.to_string())?;
+        write_str(w, self.name.as_deref().unwrap_or_default())?;
+        w.write_all(&(self.track.len() as u32).to_le_bytes())?;
+        for e in &self.track {
+            w.write_all(&e.time.timestamp().to_le_bytes())?;
+            w.write_all(&[e.indicator.map_or(0, |i| i.to_str() as u8)])?;
+            write_str(w, e.status.to_str())?;
+            w.write_all(&e.location.lat().to_le_bytes())?;
+            w.write_all(&e.location.lng().to_le_bytes())?;
+            write_i16(w, Some(e.max_sustained_wind))?;
+            write_i16(w, e.min_pressure)?;
+            for radii in [
+                &e.wind_radii_34kts,
+                &e.wind_radii_50kts,
+                &e.wind_radii_64kts,
+            ] {
+                for r in [radii.ne, radii.se, radii.sw, radii.nw] {
+                    write_i16(w, r)?;
+                }
+            }
+            write_i16(w, e.radius_of_max_wind)?;
+            w.write_all(&[e.has_radius_of_max_wind_column as u8])?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn read_binary<R: std::io::Read>(r: &mut R) -> std::io::Result<Storm> {
+        let id = read_str(r)?.parse().map_err(invalid_data)?;
+        let name = Some(read_str(r)?).filter(|n| !n.is_empty());
+        // n isn't used to reserve space since a corrupt count would make the
+        // allocation fail before the missing entries were noticed.
+        let n = u32::from_le_bytes(read_bytes(r)?) as usize;
+        let mut track = Vec::new();
+        for _ in 0..n {
+            let time = Utc
+                .timestamp_opt(i64::from_le_bytes(read_bytes(r)?), 0)
+                .single()
+                .ok_or_else(|| invalid_data("invalid time"))?;
+            let indicator = match read_bytes::<R, 1>(r)? {
+                [0] => None,
+                [c] => Some(Indicator::from_char(c as char).map_err(invalid_data)?),
+            };
+            let status = read_str(r)?.parse().map_err(invalid_data)?;
+            let location = geo::Location::new(
+                f64::from_le_bytes(read_bytes(r)?),
+                f64::from_le_bytes(read_bytes(r)?),
+            );
+            let max_sustained_wind = read_i16(r)?.ok_or_else(|| invalid_data("missing wind"))?;
+            let min_pressure = read_i16(r)?;
+            let mut radii = || -> std::io::Result<WindRadii> {
+                Ok(WindRadii {
+                    ne: read_i16(r)?,
+                    se: read_i16(r)?,
+                    sw: read_i16(r)?,
+                    nw: read_i16(r)?,
+                })
+            };
+            let wind_radii_34kts = radii()?;
+            let wind_radii_50kts = radii()?;
+            let wind_radii_64kts = radii()?;
+            track.push(TrackEntry {
+                time,
+                indicator,
+                status,
+                basin: atcf::Basin::containing(&location),
+                location,
+                max_sustained_wind,
+                min_pressure,
+                wind_radii_34kts,
+                wind_radii_50kts,
+                wind_radii_64kts,
+                radius_of_max_wind: read_i16(r)?,
+                has_radius_of_max_wind_column: read_bytes::<R, 1>(r)? != [0],
+            });
+        }
+        Ok(Storm { id, name, track })
+    }
+}
+
+fn invalid_data<E>(e: E) -> std::io::Error
+where
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+}
+
+// FIELDS lists the fields that a ParseError can name, so that the field of a
+// cached diagnostic can be restored to the same static name.
+const FIELDS: &[&str] = &[
+    "date",
+    "header",
+    "id",
+    "indicator",
+    "latitude",
+    "longitude",
+    "max_sustained_wind",
+    "min_pressure",
+    "name",
+    "num_track_entries",
+    "radius_of_max_wind",
+    "record",
+    "status",
+    "time",
+    "wind_radii_34kts",
+    "wind_radii_50kts",
+    "wind_radii_64kts",
+];
+
+impl Diagnostic {
+    pub(crate) fn write_binary<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        let e = &self.error;
+        w.write_all(&e.line.to_le_bytes())?;
+        write_str(
+            w,
+            &e.storm
+                .as_ref()
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+        )?;
+        let column = e.column.map_or(u32::MAX, |c| c as u32);
+        w.write_all(&column.to_le_bytes())?;
+        write_str(w, e.field)?;
+        write_long_str(w, &e.value)?;
+        write_long_str(w, &e.message)?;
+        let resolution: u8 = match self.resolution {
+            Resolution::Repaired => 0,
+            Resolution::SkippedEntry => 1,
+            Resolution::SkippedStorm => 2,
+        };
+        w.write_all(&[resolution])
+    }
+
+    pub(crate) fn read_binary<R: std::io::Read>(r: &mut R) -> std::io::Result<Diagnostic> {
+        let line = u64::from_le_bytes(read_bytes(r)?);
+        let storm = match read_str(r)?.as_str() {
+            "" => None,
+            id => Some(id.parse().map_err(invalid_data)?),
+        };
+        let column = match u32::from_le_bytes(read_bytes(r)?) {
+            u32::MAX => None,
+            c => Some(c as usize),
+        };
+        let field = read_str(r)?;
+        let field = FIELDS
+            .iter()
+            .find(|f| **f == field)
+            .ok_or_else(|| invalid_data("unknown field"))?;
+        let value = read_long_str(r)?;
+        let message = read_long_str(r)?;
+        let resolution = match read_bytes(r)? {
+            [0] => Resolution::Repaired,
+            [1] => Resolution::SkippedEntry,
+            [2] => Resolution::SkippedStorm,
+            _ => return Err(invalid_data("invalid resolution")),
+        };
+        Ok(Diagnostic {
+            error: ParseError {
+                line,
+                storm,
+                column,
+                field,
+                value,
+                message,
+            },
+            resolution,
+        })
+    }
+}
+
+fn write_str<W: std::io::Write>(w: &mut W, s: &str) -> std::io::Result<()> {
+    let len = u8::try_from(s.len()).map_err(|_| invalid_data("string too long"))?;
+    w.write_all(&[len])?;
+    w.write_all(s.as_bytes())
+}
+
+fn write_i16<W: std::io::Write>(w: &mut W, v: Option<i32>) -> std::io::Result<()> {
+    let v = match v {
+        Some(v) => i16::try_from(v)
+            .ok()
+            .filter(|&v| v != MISSING)
+            .ok_or_else(|| invalid_data("value out of range"))?,
+        None => MISSING,
     };
+    w.write_all(&v.to_le_bytes())
+}
+
+fn read_bytes<R: std::io::Read, const N: usize>(r: &mut R) -> std::io::Result<[u8; N]> {
+    let mut buf = [0; N];
+    r.read_exact(&mut buf)?;
+    Ok(buf)
+}
+
+fn read_str<R: std::io::Read>(r: &mut R) -> std::io::Result<String> {
+    let [len] = read_bytes(r)?;
+    let mut buf = vec![0; len as usize];
+    r.read_exact(&mut buf)?;
+    String::from_utf8(buf).map_err(invalid_data)
+}
 
-    let v = lng[..lng.len() - 1].parse::<f64>()?;
-    let lng = match lng.chars().last().unwrap() {
-        'E' | 'e' => v,
-        'W' | 'w' => -v,
-        _ => return Err(format!("invalid longitude: {}", lng).into()),
+// Strings that may be longer than a name, such as the text of a record, are
+// written with a 32-bit length.
+fn write_long_str<W: std::io::Write>(w: &mut W, s: &str) -> std::io::Result<()> {
+    w.write_all(&(s.len() as u32).to_le_bytes())?;
+    w.write_all(s.as_bytes())
+}
+
+fn read_long_str<R: std::io::Read>(r: &mut R) -> std::io::Result<String> {
+    let len = u32::from_le_bytes(read_bytes(r)?) as u64;
+    // The buffer grows as bytes arrive rather than trusting len up front.
+    let mut buf = vec![];
+    std::io::Read::read_to_end(&mut std::io::Read::take(&mut *r, len), &mut buf)?;
+    if buf.len() as u64 != len {
+        return Err(std::io::ErrorKind::UnexpectedEof.into());
+    }
+    String::from_utf8(buf).map_err(invalid_data)
+}
+
+fn read_i16<R: std::io::Read>(r: &mut R) -> std::io::Result<Option<i32>> {
+    match i16::from_le_bytes(read_bytes(r)?) {
+        MISSING => Ok(None),
+        v => Ok(Some(v as i32)),
+    }
+}
+
+fn parse_latitude(lat: &str) -> Result<f64, ValueError> {
+    let invalid = || ValueError::new("latitude", lat);
+    let v = match lat.get(..lat.len().saturating_sub(1)) {
+        Some(v) if !v.is_empty() => v.parse::<f64>().map_err(|_| invalid())?,
+        _ => return Err(invalid()),
     };
+    match lat.chars().last() {
+        Some('N' | 'n') => Ok(v),
+        Some('S' | 's') => Ok(-v),
+        _ => Err(invalid()),
+    }
+}
 
-    Ok(geo::Location::new(lat, lng))
+fn parse_longitude(lng: &str) -> Result<f64, ValueError> {
+    let invalid = || ValueError::new("longitude", lng);
+    let v = match lng.get(..lng.len().saturating_sub(1)) {
+        Some(v) if !v.is_empty() => v.parse::<f64>().map_err(|_| invalid())?,
+        _ => return Err(invalid()),
+    };
+    match lng.chars().last() {
+        Some('E' | 'e') => Ok(v),
+        Some('W' | 'w') => Ok(-v),
+        _ => Err(invalid()),
+    }
 }
 
-fn parse_wind_radii(record: &StringRecord, offset: usize) -> Result<WindRadii, Box<dyn Error>> {
-    WindRadii::from_strs(
-        record.get(offset).ok_or("missing ne")?.trim(),
-        record.get(offset + 1).ok_or("missing se")?.trim(),
-        record.get(offset + 2).ok_or("missing sw")?.trim(),
-        record.get(offset + 3).ok_or("missing nw")?.trim(),
-    )
+fn parse_wind_radii<T: Record>(
+    record: &T,
+    offset: usize,
+    field: &'static str,
+    mode: ParseMode,
+    repairs: &mut Vec<ParseError>,
+) -> Result<WindRadii, ParseError> {
+    let mut quadrant = |i: usize| {
+        repair(
+            parse_field(record, offset + i, field, |s| parse_optional_int(s, -999)),
+            mode,
+            repairs,
+        )
+    };
+    Ok(WindRadii {
+        ne: quadrant(0)?,
+        se: quadrant(1)?,
+        sw: quadrant(2)?,
+        nw: quadrant(3)?,
+    })
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -107,14 +998,75 @@ pub struct TrackEntry {
     indicator: Option<Indicator>,
     status: Status,
     location: geo::Location,
+    #[serde(default)]
+    basin: Option<atcf::Basin>,
     max_sustained_wind: i32,
     min_pressure: Option<i32>,
     wind_radii_34kts: WindRadii,
     wind_radii_50kts: WindRadii,
     wind_radii_64kts: WindRadii,
+    radius_of_max_wind: Option<i32>,
+    // has_radius_of_max_wind_column records whether the entry was read from
+    // a file that includes the radius of maximum wind, which only files
+    // published since 2022 do, so that it is written back the same way.
+    #[serde(skip)]
+    has_radius_of_max_wind_column: bool,
 }
 
 impl TrackEntry {
+    // new creates a track entry with only a position and intensity. The
+    // remaining fields are missing until they are set with the with_*
+    // methods.
+    pub fn new(
+        time: DateTime<Utc>,
+        status: Status,
+        location: geo::Location,
+        max_sustained_wind: i32,
+    ) -> TrackEntry {
+        let none = || WindRadii::new(None, None, None, None);
+        TrackEntry {
+            time,
+            indicator: None,
+            status,
+            basin: atcf::Basin::containing(&location),
+            location,
+            max_sustained_wind,
+            min_pressure: None,
+            wind_radii_34kts: none(),
+            wind_radii_50kts: none(),
+            wind_radii_64kts: none(),
+            radius_of_max_wind: None,
+            has_radius_of_max_wind_column: false,
+        }
+    }
+
+    pub fn with_indicator(mut self, indicator: Option<Indicator>) -> TrackEntry {
+        self.indicator = indicator;
+        self
+    }
+
+    pub fn with_min_pressure(mut self, mb: Option<i32>) -> TrackEntry {
+        self.min_pressure = mb;
+        self
+    }
+
+    // with_wind_radii sets the radii of 34, 50 or 64 kt winds. Other speeds
+    // are ignored.
+    pub fn with_wind_radii(mut self, kts: i32, radii: WindRadii) -> TrackEntry {
+        match kts {
+            34 => self.wind_radii_34kts = radii,
+            50 => self.wind_radii_50kts = radii,
+            64 => self.wind_radii_64kts = radii,
+            _ => {}
+        }
+        self
+    }
+
+    pub fn with_radius_of_max_wind(mut self, nm: Option<i32>) -> TrackEntry {
+        self.radius_of_max_wind = nm;
+        self
+    }
+
     pub fn time(&self) -> DateTime<Utc> {
         self.time
     }
@@ -131,6 +1083,12 @@ impl TrackEntry {
         &self.location
     }
 
+    // basin is the basin the storm was in at the time of the entry, which
+    // may differ from the basin it was numbered in.
+    pub fn basin(&self) -> Option<atcf::Basin> {
+        self.basin
+    }
+
     pub fn wind_radii_34kts(&self) -> &WindRadii {
         &self.wind_radii_34kts
     }
@@ -143,6 +1101,24 @@ impl TrackEntry {
         &self.wind_radii_64kts
     }
 
+    // wind_radii returns the radii of winds of at least kts, which must be
+    // one of 34, 50 or 64.
+    pub fn wind_radii(&self, kts: i32) -> Option<&WindRadii> {
+        match kts {
+            34 => Some(&self.wind_radii_34kts),
+            50 => Some(&self.wind_radii_50kts),
+            64 => Some(&self.wind_radii_64kts),
+            _ => None,
+        }
+    }
+
+    // wind_field returns the polygon around the entry's location enclosing
+    // winds of at least kts, which must be one of 34, 50 or 64. See
+    // WindRadii::polygon.
+    pub fn wind_field(&self, kts: i32, steps: usize) -> Option<Vec<geo::Location>> {
+        self.wind_radii(kts)?.polygon(&self.location, steps)
+    }
+
     pub fn max_sustained_wind(&self) -> i32 {
         self.max_sustained_wind
     }
@@ -151,39 +1127,119 @@ impl TrackEntry {
         self.min_pressure
     }
 
-    fn from_record(record: &StringRecord) -> Result<TrackEntry, Box<dyn Error>> {
-        let d = NaiveDate::parse_from_str(record.get(0).ok_or("missing date")?, "%Y%m%d")?;
-        let t = NaiveTime::parse_from_str(record.get(1).ok_or("missing time")?, "%H%M")?;
+    pub fn category(&self) -> Category {
+        Category::from_wind(self.max_sustained_wind)
+    }
+
+    // ace returns the entry's contribution to Accumulated Cyclone Energy.
+    // Following the NHC, only synoptic times (00, 06, 12 and 18 UTC) count
+    // and only while the system is a tropical or subtropical storm or a
+    // hurricane.
+    pub fn ace(&self) -> f64 {
+        let synoptic = self.time.minute() == 0 && self.time.hour().is_multiple_of(6);
+        let counts = matches!(
+            self.status,
+            Status::TropicalStorm | Status::Hurricane | Status::SubtropicalStorm
+        );
+        if synoptic && counts && self.max_sustained_wind >= 34 {
+            (self.max_sustained_wind as f64).powi(2) / 1e4
+        } else {
+            0.0
+        }
+    }
+
+    pub fn radius_of_max_wind(&self) -> Option<geo::Distance> {
+        self.radius_of_max_wind
+            .map(|r| geo::Distance::from_nautical_miles(r as f64))
+    }
+
+    fn write_hurdat2<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<()> {
+        let lat = self.location.lat();
+        let lng = self.location.lng();
+        write!(
+            w,
+            "{}, {}, {}, {}, {:>4.1}{}, {:>5.1}{}, {:>3}, {:>4}",
+            self.time.format("%Y%m%d"),
+            self.time.format("%H%M"),
+            self.indicator.map_or(' ', |i| i.to_str()),
+            self.status.to_str(),
+            lat.abs(),
+            if lat.is_sign_negative() { 'S' } else { 'N' },
+            lng.abs(),
+            if lng.is_sign_negative() { 'W' } else { 'E' },
+            self.max_sustained_wind,
+            self.min_pressure.unwrap_or(-999),
+        )?;
+        for radii in [
+            &self.wind_radii_34kts,
+            &self.wind_radii_50kts,
+            &self.wind_radii_64kts,
+        ] {
+            for r in [radii.ne, radii.se, radii.sw, radii.nw] {
+                write!(w, ", {:>4}", r.unwrap_or(-999))?;
+            }
+        }
+        if self.has_radius_of_max_wind_column || self.radius_of_max_wind.is_some() {
+            write!(w, ", {:>4}", self.radius_of_max_wind.unwrap_or(-999))?;
+        }
+        writeln!(w, ",")
+    }
+
+    fn from_record<T: Record>(
+        record: &T,
+        mode: ParseMode,
+        repairs: &mut Vec<ParseError>,
+    ) -> Result<TrackEntry, ParseError> {
+        let d = parse_field(record, 0, "date", |s| {
+            NaiveDate::parse_from_str(s, "%Y%m%d")
+        })?;
+        let t = parse_field(record, 1, "time", |s| NaiveTime::parse_from_str(s, "%H%M"))?;
         let time = Utc.from_utc_datetime(&NaiveDateTime::new(d, t));
 
+        let indicator = repair(
+            parse_field(record, 2, "indicator", |s| match s {
+                "" => Ok(None),
+                s => Indicator::from_str(s).map(Some),
+            }),
+            mode,
+            repairs,
+        )?;
+        let status = parse_field(record, 3, "status", |s| s.parse())?;
+        let location = geo::Location::new(
+            parse_field(record, 4, "latitude", parse_latitude)?,
+            parse_field(record, 5, "longitude", parse_longitude)?,
+        );
+
+        // Files published before 2022 do not include the radius of maximum
+        // wind, leaving only the trailing empty column.
+        let has_radius_of_max_wind_column =
+            !matches!(record.get(20).map(str::trim), None | Some(""));
         Ok(TrackEntry {
             time,
-            indicator: match record.get(2).ok_or("missing indicator")?.trim() {
-                "" => None,
-                s => Some(Indicator::from_str(s)?),
-            },
-            status: record.get(3).ok_or("missing status")?.trim().parse()?,
-            location: parse_location(
-                record.get(4).ok_or("missing latitude")?.trim(),
-                record.get(5).ok_or("missing longitude")?.trim(),
+            indicator,
+            status,
+            basin: atcf::Basin::containing(&location),
+            location,
+            max_sustained_wind: parse_field(record, 6, "max_sustained_wind", |s| s.parse())?,
+            min_pressure: repair(
+                parse_field(record, 7, "min_pressure", |s| parse_optional_int(s, -999)),
+                mode,
+                repairs,
             )?,
-            max_sustained_wind: record
-                .get(6)
-                .ok_or("max sustained wind")?
-                .trim()
-                .parse()
-                .map_err(|_| "invalid max sustained wind")?,
-            min_pressure: parse_optional_int(
-                record.get(7).ok_or("missing min_pressure")?.trim(),
-                -999,
-            )
-            .map_err(|_| "invalid min_pressure")?,
-            wind_radii_34kts: parse_wind_radii(record, 8)
-                .map_err(|_| "invalid wind_radii_34kts")?,
-            wind_radii_50kts: parse_wind_radii(record, 12)
-                .map_err(|_| "invalid wind_radii_50kts")?,
-            wind_radii_64kts: parse_wind_radii(record, 16)
-                .map_err(|_| "invalid wind_radii_64kts")?,
+            wind_radii_34kts: parse_wind_radii(record, 8, "wind_radii_34kts", mode, repairs)?,
+            wind_radii_50kts: parse_wind_radii(record, 12, "wind_radii_50kts", mode, repairs)?,
+            wind_radii_64kts: parse_wind_radii(record, 16, "wind_radii_64kts", mode, repairs)?,
+            radius_of_max_wind: match has_radius_of_max_wind_column {
+                false => None,
+                true => repair(
+                    parse_field(record, 20, "radius_of_max_wind", |s| {
+                        parse_optional_int(s, -999)
+                    }),
+                    mode,
+                    repairs,
+                )?,
+            },
+            has_radius_of_max_wind_column,
         })
     }
 }
@@ -233,7 +1289,7 @@ impl Indicator {
         }
     }
 
-    pub fn from_char(c: char) -> Result<Indicator, Box<dyn Error>> {
+    pub fn from_char(c: char) -> Result<Indicator, ValueError> {
         match c {
             'C' => Ok(Indicator::ClosestApproach),
             'G' => Ok(Indicator::Genesis),
@@ -244,7 +1300,7 @@ impl Indicator {
             'S' => Ok(Indicator::StatusChange),
             'T' => Ok(Indicator::Track),
             'W' => Ok(Indicator::MaxWind),
-            _ => Err(format!("invalid indicator: {}", c).into()),
+            _ => Err(ValueError::new("indicator", &c.to_string())),
         }
     }
 }
@@ -256,11 +1312,11 @@ impl std::fmt::Display for Indicator {
 }
 
 impl FromStr for Indicator {
-    type Err = Box<dyn Error>;
+    type Err = ValueError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() != 1 {
-            return Err(format!("invalid indicator: {}", s).into());
+        if s.chars().count() != 1 {
+            return Err(ValueError::new("indicator", s));
         }
         Indicator::from_char(s.chars().next().unwrap())
     }
@@ -331,7 +1387,7 @@ impl Status {
 }
 
 impl FromStr for Status {
-    type Err = Box<dyn Error>;
+    type Err = ValueError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -344,7 +1400,7 @@ impl FromStr for Status {
             "LO" => Ok(Status::Low),
             "WV" => Ok(Status::TropicalWave),
             "DB" => Ok(Status::Disturbance),
-            _ => Err(format!("invalid status: {}", s).into()),
+            _ => Err(ValueError::new("status", s)),
         }
     }
 }
@@ -384,12 +1440,331 @@ impl<'de> de::Deserialize<'de> for Status {
     }
 }
 
-fn parse_optional_int(s: &str, empty: i32) -> Result<Option<i32>, Box<dyn Error>> {
+// Category is the Saffir-Simpson category of a cyclone, extended with the
+// depression and storm classifications below hurricane strength. Categories
+// are ordered by intensity.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Category {
+    TropicalDepression,
+    TropicalStorm,
+    One,
+    Two,
+    Three,
+    Four,
+    Five,
+}
+
+impl Category {
+    pub fn all() -> [Category; 7] {
+        [
+            Category::TropicalDepression,
+            Category::TropicalStorm,
+            Category::One,
+            Category::Two,
+            Category::Three,
+            Category::Four,
+            Category::Five,
+        ]
+    }
+
+    // from_wind returns the category for a maximum sustained wind in knots.
+    pub fn from_wind(kts: i32) -> Category {
+        match kts {
+            i32::MIN..=33 => Category::TropicalDepression,
+            34..=63 => Category::TropicalStorm,
+            64..=82 => Category::One,
+            83..=95 => Category::Two,
+            96..=112 => Category::Three,
+            113..=136 => Category::Four,
+            _ => Category::Five,
+        }
+    }
+
+    pub fn is_hurricane(&self) -> bool {
+        *self >= Category::One
+    }
+
+    pub fn is_major(&self) -> bool {
+        *self >= Category::Three
+    }
+
+    pub fn to_str(&self) -> &str {
+        match self {
+            Category::TropicalDepression => "TD",
+            Category::TropicalStorm => "TS",
+            Category::One => "1",
+            Category::Two => "2",
+            Category::Three => "3",
+            Category::Four => "4",
+            Category::Five => "5",
+        }
+    }
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
+impl FromStr for Category {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "TD" => Ok(Category::TropicalDepression),
+            "TS" => Ok(Category::TropicalStorm),
+            "1" => Ok(Category::One),
+            "2" => Ok(Category::Two),
+            "3" => Ok(Category::Three),
+            "4" => Ok(Category::Four),
+            "5" => Ok(Category::Five),
+            _ => Err(ValueError::new("category", s)),
+        }
+    }
+}
+
+impl ser::Serialize for Category {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        self.to_str().serialize(serializer)
+    }
+}
+
+struct CategoryVisitor;
+
+impl<'de> de::Visitor<'de> for CategoryVisitor {
+    type Value = Category;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(formatter, "a category string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Category::from_str(v).map_err(serde::de::Error::custom)
+    }
+}
+
+impl<'de> de::Deserialize<'de> for Category {
+    fn deserialize<D>(d: D) -> Result<Category, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        d.deserialize_str(CategoryVisitor)
+    }
+}
+
+// Metrics summarizes the intensity and lifetime of a storm.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Metrics {
+    peak_category: Category,
+    peak_wind: i32,
+    min_pressure: Option<i32>,
+    #[serde(rename = "lifetime_hours")]
+    lifetime: f64,
+    #[serde(rename = "hours_in_category")]
+    time_in_category: BTreeMap<Category, f64>,
+    #[serde(rename = "track_length_nm")]
+    track_length: f64,
+    ace: f64,
+}
+
+impl Metrics {
+    fn from_track(track: &[TrackEntry]) -> Metrics {
+        let peak_wind = track
+            .iter()
+            .map(|e| e.max_sustained_wind)
+            .max()
+            .unwrap_or_default();
+
+        // Each segment of the track is split evenly between the categories
+        // at either end of it.
+        let mut time_in_category = BTreeMap::new();
+        for w in track.windows(2) {
+            let hours = (w[1].time - w[0].time).num_seconds() as f64 / 3600.0;
+            for e in w {
+                *time_in_category.entry(e.category()).or_insert(0.0) += hours / 2.0;
+            }
+        }
+
+        Metrics {
+            peak_category: Category::from_wind(peak_wind),
+            peak_wind,
+            min_pressure: track.iter().filter_map(|e| e.min_pressure).min(),
+            lifetime: match (track.first(), track.last()) {
+                (Some(a), Some(b)) => (b.time - a.time).num_seconds() as f64 / 3600.0,
+                _ => 0.0,
+            },
+            time_in_category,
+            track_length: track
+                .windows(2)
+                .map(|w| geo::Location::distance_between(&w[0].location, &w[1].location))
+                .map(|d| d.in_nautical_miles())
+                .sum(),
+            ace: track.iter().map(|e| e.ace()).sum(),
+        }
+    }
+
+    pub fn peak_category(&self) -> Category {
+        self.peak_category
+    }
+
+    pub fn peak_wind(&self) -> i32 {
+        self.peak_wind
+    }
+
+    pub fn min_pressure(&self) -> Option<i32> {
+        self.min_pressure
+    }
+
+    pub fn lifetime(&self) -> Duration {
+        Duration::seconds((self.lifetime * 3600.0).round() as i64)
+    }
+
+    pub fn time_in_category(&self, category: Category) -> Duration {
+        let hours = self
+            .time_in_category
+            .get(&category)
+            .copied()
+            .unwrap_or_default();
+        Duration::seconds((hours * 3600.0).round() as i64)
+    }
+
+    pub fn track_length(&self) -> geo::Distance {
+        geo::Distance::from_nautical_miles(self.track_length)
+    }
+
+    // ace returns the Accumulated Cyclone Energy in units of 10^4 kt^2.
+    pub fn ace(&self) -> f64 {
+        self.ace
+    }
+}
+
+// STALL_SPEED is the translation speed, in knots, below which a storm is
+// considered to be stalled.
+const STALL_SPEED: f64 = 2.0;
+
+// Motion describes a storm's movement over one segment of its track, between
+// two consecutive track entries.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Motion {
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    #[serde(rename = "distance_nm")]
+    distance: f64,
+    #[serde(rename = "speed_kt")]
+    speed: f64,
+    #[serde(rename = "heading_deg")]
+    heading: Option<f64>,
+    stalled: bool,
+    looping: bool,
+}
+
+impl Motion {
+    // from_track returns the motion over each segment of the track. A segment
+    // is stalled if the storm moved slower than STALL_SPEED over it. Segments
+    // are looping if they are part of a loop, which is closed when the track
+    // crosses over an earlier segment.
+    fn from_track(track: &[TrackEntry]) -> Vec<Motion> {
+        let mut motion = track
+            .windows(2)
+            .map(|w| {
+                let (a, b) = (&w[0], &w[1]);
+                let distance =
+                    geo::Location::distance_between(&a.location, &b.location).in_nautical_miles();
+                let hours = (b.time - a.time).num_seconds() as f64 / 3600.0;
+                let speed = if hours > 0.0 { distance / hours } else { 0.0 };
+                Motion {
+                    start: a.time,
+                    end: b.time,
+                    distance,
+                    speed,
+                    heading: (distance > 0.0)
+                        .then(|| a.location.bearing_to(&b.location).in_degrees()),
+                    stalled: speed < STALL_SPEED,
+                    looping: false,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // Longitudes are unwrapped relative to the first entry so that tracks
+        // crossing the antimeridian stay continuous.
+        let reference = track.first().map_or(0.0, |e| e.location.lng());
+        let pts = track
+            .iter()
+            .map(|e| {
+                let lng =
+                    reference + (e.location.lng() - reference + 540.0).rem_euclid(360.0) - 180.0;
+                (lng, e.location.lat())
+            })
+            .collect::<Vec<_>>();
+        for i in 2..motion.len() {
+            let crossed = (0..i - 1)
+                .rev()
+                .find(|&j| segments_cross(pts[j], pts[j + 1], pts[i], pts[i + 1]));
+            if let Some(j) = crossed {
+                for m in &mut motion[j..=i] {
+                    m.looping = true;
+                }
+            }
+        }
+        motion
+    }
+
+    pub fn start(&self) -> DateTime<Utc> {
+        self.start
+    }
+
+    pub fn end(&self) -> DateTime<Utc> {
+        self.end
+    }
+
+    pub fn distance(&self) -> geo::Distance {
+        geo::Distance::from_nautical_miles(self.distance)
+    }
+
+    // speed returns the translation speed in knots.
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    // heading returns the initial bearing of the segment, or None if the
+    // storm didn't move.
+    pub fn heading(&self) -> Option<geo::Bearing> {
+        self.heading.map(geo::Bearing::from_degrees)
+    }
+
+    pub fn is_stalled(&self) -> bool {
+        self.stalled
+    }
+
+    pub fn is_looping(&self) -> bool {
+        self.looping
+    }
+}
+
+// segments_cross reports whether the segment from a to b properly crosses the
+// segment from c to d.
+fn segments_cross(a: (f64, f64), b: (f64, f64), c: (f64, f64), d: (f64, f64)) -> bool {
+    let orient = |p: (f64, f64), q: (f64, f64), r: (f64, f64)| {
+        ((q.0 - p.0) * (r.1 - p.1) - (q.1 - p.1) * (r.0 - p.0)).signum()
+    };
+    let (o1, o2) = (orient(a, b, c), orient(a, b, d));
+    let (o3, o4) = (orient(c, d, a), orient(c, d, b));
+    o1 * o2 < 0.0 && o3 * o4 < 0.0
+}
+
+fn parse_optional_int(s: &str, empty: i32) -> Result<Option<i32>, ParseIntError> {
     let v = s.parse::<i32>()?;
     Ok(if v == empty { None } else { Some(v) })
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WindRadii {
     ne: Option<i32>,
     se: Option<i32>,
@@ -398,12 +1773,10 @@ pub struct WindRadii {
 }
 
 impl WindRadii {
-    fn from_strs(ne: &str, se: &str, sw: &str, nw: &str) -> Result<WindRadii, Box<dyn Error>> {
-        let ne = parse_optional_int(ne, -999).map_err(|_| format!("invalid ne: {}", ne))?;
-        let se = parse_optional_int(se, -999).map_err(|_| format!("invalid se: {}", se))?;
-        let sw = parse_optional_int(sw, -999).map_err(|_| format!("invalid sw: {}", sw))?;
-        let nw = parse_optional_int(nw, -999).map_err(|_| format!("invalid nw: {}", nw))?;
-        Ok(WindRadii { ne, se, sw, nw })
+    // new creates wind radii from the extent of the winds in each quadrant,
+    // in nautical miles.
+    pub fn new(ne: Option<i32>, se: Option<i32>, sw: Option<i32>, nw: Option<i32>) -> WindRadii {
+        WindRadii { ne, se, sw, nw }
     }
 
     pub fn max_radius(&self) -> Option<geo::Distance> {
@@ -422,45 +1795,369 @@ impl WindRadii {
         }
         r.map(|r| geo::Distance::from_nautical_miles(r as f64))
     }
+
+    pub fn ne(&self) -> Option<geo::Distance> {
+        self.ne
+            .map(|r| geo::Distance::from_nautical_miles(r as f64))
+    }
+
+    pub fn se(&self) -> Option<geo::Distance> {
+        self.se
+            .map(|r| geo::Distance::from_nautical_miles(r as f64))
+    }
+
+    pub fn sw(&self) -> Option<geo::Distance> {
+        self.sw
+            .map(|r| geo::Distance::from_nautical_miles(r as f64))
+    }
+
+    pub fn nw(&self) -> Option<geo::Distance> {
+        self.nw
+            .map(|r| geo::Distance::from_nautical_miles(r as f64))
+    }
+
+    // polygon returns the outline of the wind field around center, going
+    // clockwise from north. Each quadrant is an arc at that quadrant's radius
+    // made of steps segments. HURDAT2 uses 0 for quadrants the winds don't
+    // reach, so those collapse to center. A missing quadrant is treated the
+    // same way when others are known. It returns None if no quadrant has a
+    // non-zero radius. The ring is not closed.
+    pub fn polygon(&self, center: &geo::Location, steps: usize) -> Option<Vec<geo::Location>> {
+        let radii = [self.ne, self.se, self.sw, self.nw].map(|r| r.unwrap_or(0));
+        if radii.iter().all(|&r| r <= 0) {
+            return None;
+        }
+
+        let steps = steps.max(1);
+        let mut points: Vec<geo::Location> = vec![];
+        for (q, &r) in radii.iter().enumerate() {
+            if r <= 0 {
+                if points.last() != Some(center) {
+                    points.push(center.clone());
+                }
+                continue;
+            }
+            let distance = geo::Distance::from_nautical_miles(r as f64);
+            for i in 0..=steps {
+                let deg = 90.0 * q as f64 + 90.0 * i as f64 / steps as f64;
+                points.push(center.destination_of(geo::Bearing::from_degrees(deg), distance));
+            }
+        }
+        if points.len() > 1 && points.first() == points.last() {
+            points.pop();
+        }
+        Some(points)
+    }
 }
 
 struct Header {
     id: atcf::Id,
     name: Option<String>,
-    num_track_entries: usize,
+    num_track_entries: Option<usize>,
+    line: u64,
 }
 
 impl Header {
-    fn from_record(record: &StringRecord) -> Result<Header, Box<dyn Error>> {
+    // is_header reports whether a record looks like a storm header rather
+    // than a track entry. Headers have exactly 4 columns and start with a
+    // storm id rather than a date, so a track entry that was cut short is
+    // not mistaken for one. A header with a malformed id is still a header.
+    fn is_header<T: Record>(record: &T) -> bool {
+        record.len() == 4
+            && record
+                .get(0)
+                .is_some_and(|s| NaiveDate::parse_from_str(s.trim(), "%Y%m%d").is_err())
+    }
+
+    fn from_record<T: Record>(
+        record: &T,
+        mode: ParseMode,
+        repairs: &mut Vec<ParseError>,
+    ) -> Result<Header, ParseError> {
         if record.len() != 4 {
-            return Err(format!("storm header has {} columns, not 4.", record.len()).into());
+            return Err(ParseError::new(
+                record,
+                None,
+                "header",
+                record.get(0).unwrap_or_default(),
+                format!("storm header has {} columns, not 4", record.len()),
+            ));
         }
 
-        let id = record
-            .get(0)
-            .ok_or("missing id")?
-            .trim()
-            .parse::<atcf::Id>()?;
-        let name = match record.get(1).ok_or("missing name")?.trim() {
+        let id = parse_field(record, 0, "id", |s| s.parse::<atcf::Id>())?;
+        let name = match get_field(record, 1, "name").map_err(|e| e.with_storm(&id))? {
             "UNAMED" => None,
             v => Some(v.to_owned()),
         };
-        let num_track_entries = record
-            .get(2)
-            .ok_or("missing num entries")?
-            .trim()
-            .parse::<usize>()?;
+        let num_track_entries = repair(
+            parse_field(record, 2, "num_track_entries", |s| {
+                s.parse::<usize>().map(Some)
+            }),
+            mode,
+            repairs,
+        )
+        .map_err(|e| e.with_storm(&id))?;
         Ok(Header {
             id,
             name,
             num_track_entries,
+            line: record.line(),
         })
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::WindRadii;
+    use super::{
+        Category, Hurdat2Reader, Hurdat2Stream, Hurdat2Writer, ParseMode, Resolution, Status,
+        WindRadii,
+    };
+    use crate::geo;
+    use chrono::{Duration, TimeZone, Utc};
+    use tokio_stream::StreamExt;
+
+    const SAMPLE: &str = "\
+AL011851,            UNNAMED,      2,
+18510625, 0000,  , HU, 28.0N,  94.8W,  80, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+18510625, 0600,  , HU, 28.0N,  95.4W,  80, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+AL092023,             IDALIA,      3,
+20230830, 0600,  , HU, 28.9N,  84.1W, 110,  942,  150,  130,   70,  100,   70,   60,   40,   50,   40,   30,   25,   30,
+20230830, 1145, L, HU, 29.9N,  83.6W, 100,  949,  150,  130,   60,   90,   70,   60,   40,   50,   40,   30,   25,   30,
+20230830, 1800,  , TS, 31.0N,  82.6W,  60,  978,  150,  140,   50,   60,   50,   40,    0,    0,    0,    0,    0,    0,
+";
+
+    #[test]
+    fn reader() {
+        let storms = Hurdat2Reader::new(SAMPLE.as_bytes(), ParseMode::Strict)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(storms.len(), 2);
+        assert_eq!(storms[0].id().to_string(), "AL011851");
+        assert_eq!(storms[0].track().len(), 2);
+        assert_eq!(storms[1].name(), Some("IDALIA"));
+        assert_eq!(storms[1].track()[2].status(), Status::TropicalStorm);
+        assert_eq!(storms[1].track()[1].min_pressure(), Some(949));
+    }
+
+    #[test]
+    fn reader_truncated() {
+        let src = &SAMPLE[..SAMPLE.rfind("20230830, 1800").unwrap()];
+        let mut storms = Hurdat2Reader::new(src.as_bytes(), ParseMode::Strict);
+        assert!(storms.next().unwrap().is_ok());
+        assert!(storms.next().unwrap().is_err());
+        assert!(storms.next().is_none());
+    }
+
+    #[test]
+    fn reader_strict_error() {
+        let src = SAMPLE.replace(" 942,", " 9x2,");
+        let err = Hurdat2Reader::new(src.as_bytes(), ParseMode::Strict)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_err();
+        let err = match err {
+            crate::Error::Parse(err) => err,
+            err => panic!("unexpected error: {}", err),
+        };
+        assert_eq!(err.line(), 5);
+        assert_eq!(
+            err.storm().map(|id| id.to_string()),
+            Some("AL092023".into())
+        );
+        assert_eq!(err.column(), Some(7));
+        assert_eq!(err.value(), "9x2");
+    }
+
+    #[test]
+    fn reader_lenient() {
+        let src = SAMPLE
+            .replace(" 942,", " 9x2,")
+            .replace(
+                "20230830, 1145, L, HU, 29.9N",
+                "20230830, 1145, L, XX, 29.9N",
+            )
+            .replace("AL011851", "XX011851");
+        let mut reader = Hurdat2Reader::new(src.as_bytes(), ParseMode::Lenient);
+        let storms = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(storms.len(), 1);
+        assert_eq!(storms[0].track().len(), 2);
+        assert_eq!(storms[0].track()[0].min_pressure(), None);
+
+        let diagnostics = reader
+            .diagnostics()
+            .iter()
+            .map(|d| (d.error().line(), d.error().field(), d.resolution()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            vec![
+                (1, "id", Resolution::SkippedStorm),
+                (5, "min_pressure", Resolution::Repaired),
+                (6, "status", Resolution::SkippedEntry),
+                (4, "num_track_entries", Resolution::Repaired),
+            ]
+        );
+    }
+
+    #[test]
+    fn reader_lenient_truncated_entry() {
+        let src = SAMPLE.replace(
+            "20230830, 1145, L, HU, 29.9N,  83.6W, 100,  949,  150,  130,   60,   90,   70,   60,   40,   50,   40,   30,   25,   30,",
+            "20230830, 1145, L,",
+        );
+        let mut reader = Hurdat2Reader::new(src.as_bytes(), ParseMode::Lenient);
+        let storms = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(storms.len(), 2);
+        assert_eq!(storms[1].name(), Some("IDALIA"));
+        assert_eq!(storms[1].track().len(), 2);
+
+        let diagnostics = reader
+            .diagnostics()
+            .iter()
+            .map(|d| (d.error().line(), d.error().field(), d.resolution()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            diagnostics,
+            vec![
+                (6, "status", Resolution::SkippedEntry),
+                (4, "num_track_entries", Resolution::Repaired),
+            ]
+        );
+    }
+
+    fn write_hurdat2(src: &str) -> String {
+        let mut w = Hurdat2Writer::new(Vec::new());
+        for storm in Hurdat2Reader::new(src.as_bytes(), ParseMode::Strict) {
+            w.write(&storm.unwrap()).unwrap();
+        }
+        String::from_utf8(w.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn writer_round_trip() {
+        assert_eq!(write_hurdat2(SAMPLE), SAMPLE);
+    }
+
+    #[test]
+    fn writer_round_trip_radius_of_max_wind() {
+        // Files published since 2022 add the radius of maximum wind, which
+        // is -999 where it is missing.
+        let src = SAMPLE
+            .replace(" -999,\n", " -999, -999,\n")
+            .replace("   30,\n", "   30,   15,\n")
+            .replace("    0,\n", "    0,   40,\n");
+        assert_eq!(write_hurdat2(&src), src);
+        let storms = Hurdat2Reader::new(src.as_bytes(), ParseMode::Strict)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(storms[0].track()[0].radius_of_max_wind(), None);
+        assert_eq!(
+            storms[1].track()[0]
+                .radius_of_max_wind()
+                .map(|r| r.in_nautical_miles()),
+            Some(15.0)
+        );
+    }
+
+    #[test]
+    fn position_at() {
+        let storm = Hurdat2Reader::new(SAMPLE.as_bytes(), ParseMode::Strict)
+            .nth(1)
+            .unwrap()
+            .unwrap();
+        let t = |h, m| Utc.with_ymd_and_hms(2023, 8, 30, h, m, 0).unwrap();
+
+        let p = storm.position_at(t(11, 45)).unwrap();
+        assert_eq!(p.max_sustained_wind(), 100.0);
+        assert_eq!(p.location().lat(), 29.9);
+
+        let p = storm.position_at(t(15, 0)).unwrap();
+        assert!((p.max_sustained_wind() - 79.2).abs() < 1e-9);
+        assert!((p.min_pressure().unwrap() - 964.08).abs() < 1e-9);
+        assert!(p.location().lat() > 29.9 && p.location().lat() < 31.0);
+        assert!(p.location().lng() > -83.6 && p.location().lng() < -82.6);
+
+        assert!(storm.position_at(t(5, 0)).is_none());
+        assert!(storm.position_at(t(19, 0)).is_none());
+
+        let track = storm.resample(Duration::hours(1));
+        assert_eq!(track.len(), 13);
+        assert_eq!(track[0].time(), t(6, 0));
+        assert_eq!(track[12].time(), t(18, 0));
+
+        let track = storm.resample(Duration::minutes(90));
+        assert_eq!(track[0].time(), t(6, 0));
+        assert_eq!(track[1].time(), t(7, 30));
+    }
+
+    #[test]
+    fn metrics() {
+        let storms = Hurdat2Reader::new(SAMPLE.as_bytes(), ParseMode::Strict)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        let m = storms[1].metrics();
+        assert_eq!(m.peak_category(), Category::Three);
+        assert_eq!(m.peak_wind(), 110);
+        assert_eq!(m.min_pressure(), Some(942));
+        assert_eq!(m.lifetime(), Duration::hours(12));
+        assert_eq!(
+            m.time_in_category(Category::Three),
+            Duration::seconds(345 * 60 + 375 * 30)
+        );
+        assert_eq!(
+            m.time_in_category(Category::TropicalStorm),
+            Duration::seconds(375 * 30)
+        );
+        assert!((m.ace() - (110.0f64.powi(2) + 60.0f64.powi(2)) / 1e4).abs() < 1e-9);
+        assert!(m.track_length().in_nautical_miles() > 100.0);
+
+        assert_eq!(Category::from_wind(63), Category::TropicalStorm);
+        assert_eq!(Category::from_wind(137), Category::Five);
+        assert!(Category::Three.is_major() && !Category::Two.is_major());
+    }
+
+    #[test]
+    fn motion() {
+        const LOOP: &str = "\
+AL992020,             SAMPLE,      6,
+20200901, 0000,  , TS, 20.0N,  60.0W,  40, 1000, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200901, 0600,  , TS, 20.0N,  61.0W,  40, 1000, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200901, 1200,  , TS, 21.0N,  61.0W,  40, 1000, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200901, 1800,  , TS, 21.0N,  60.5W,  40, 1000, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200902, 0000,  , TS, 19.5N,  60.5W,  40, 1000, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200902, 0600,  , TS, 19.5N,  60.5W,  40, 1000, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+";
+        let storm = Hurdat2Reader::new(LOOP.as_bytes(), ParseMode::Strict)
+            .next()
+            .unwrap()
+            .unwrap();
+        let motion = storm.motion();
+        assert_eq!(motion.len(), 5);
+
+        let heading = motion[0].heading().unwrap().in_degrees();
+        assert!((heading - 270.0).abs() < 0.5);
+        assert!((motion[0].speed() - 56.4 / 6.0).abs() < 0.1);
+        assert!(motion[1].heading().unwrap().in_degrees().abs() < 1e-9);
+        assert!((motion[3].heading().unwrap().in_degrees() - 180.0).abs() < 1e-9);
+
+        let looping = motion.iter().map(|m| m.is_looping()).collect::<Vec<_>>();
+        assert_eq!(looping, vec![true, true, true, true, false]);
+        assert!(!motion[3].is_stalled());
+        assert!(motion[4].is_stalled());
+        assert!(motion[4].heading().is_none());
+    }
+
+    #[tokio::test]
+    async fn stream() {
+        let storms = Hurdat2Stream::from_reader(SAMPLE.as_bytes(), ParseMode::Strict)
+            .collect::<Result<Vec<_>, _>>()
+            .await
+            .unwrap();
+        assert_eq!(storms.len(), 2);
+        assert_eq!(storms[1].id().to_string(), "AL092023");
+        assert_eq!(storms[1].track().len(), 3);
+    }
+
     #[test]
     fn max_radius() {
         let wr = WindRadii {
@@ -487,4 +2184,30 @@ mod test {
         };
         assert_eq!(wr.max_radius(), None);
     }
+
+    #[test]
+    fn wind_radii_polygon() {
+        let center = geo::Location::new(25.0, -80.0);
+        let wr = WindRadii {
+            ne: Some(60),
+            se: Some(30),
+            sw: Some(0),
+            nw: None,
+        };
+        let points = wr.polygon(&center, 2).unwrap();
+        assert_eq!(points.len(), 7);
+        let d = |p: &geo::Location| geo::Location::distance_between(&center, p).in_nautical_miles();
+        assert!((d(&points[0]) - 60.0).abs() < 1e-6);
+        assert!(points[0].lat() > center.lat());
+        assert!((d(&points[3]) - 30.0).abs() < 1e-6);
+        assert_eq!(points[6], center);
+
+        let wr = WindRadii {
+            ne: Some(0),
+            se: None,
+            sw: Some(0),
+            nw: Some(0),
+        };
+        assert!(wr.polygon(&center, 2).is_none());
+    }
 }
@@ -0,0 +1,558 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    str::FromStr,
+};
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+use crate::{
+    atcf::{self, Basin},
+    geo,
+    hurdat2::{Indicator, Status, Storm, TrackEntry, WindRadii},
+    Error, ValueError,
+};
+
+// Agency is one of the agencies whose best tracks IBTrACS merges. Each
+// reports its own position, wind and pressure in its own columns. The US
+// agencies (NHC, CPHC and JTWC) report 1-minute sustained winds, as HURDAT2
+// does, while most others use a 10-minute average. Wmo selects the values
+// of whichever agency is responsible for the basin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Agency {
+    Wmo,
+    #[default]
+    Usa,
+    Tokyo,
+    Cma,
+    Hko,
+    NewDelhi,
+    Reunion,
+    Bom,
+    Nadi,
+    Wellington,
+}
+
+impl Agency {
+    pub fn to_str(&self) -> &str {
+        match self {
+            Agency::Wmo => "wmo",
+            Agency::Usa => "usa",
+            Agency::Tokyo => "tokyo",
+            Agency::Cma => "cma",
+            Agency::Hko => "hko",
+            Agency::NewDelhi => "newdelhi",
+            Agency::Reunion => "reunion",
+            Agency::Bom => "bom",
+            Agency::Nadi => "nadi",
+            Agency::Wellington => "wellington",
+        }
+    }
+
+    // column returns the name of the agency's column for field, e.g.
+    // TOKYO_WIND. The WMO position is in the main LAT and LON columns.
+    fn column(&self, field: &str) -> String {
+        match (self, field) {
+            (Agency::Wmo, "LAT" | "LON") => field.to_owned(),
+            _ => format!("{}_{}", self.to_str().to_ascii_uppercase(), field),
+        }
+    }
+
+    // flag is the agency's position in the IFLAG column, which records
+    // whether each agency's values in a row are original or interpolated.
+    fn flag(&self) -> Option<usize> {
+        match self {
+            Agency::Wmo => None,
+            Agency::Usa => Some(0),
+            Agency::Tokyo => Some(1),
+            Agency::Cma => Some(2),
+            Agency::Hko => Some(3),
+            Agency::NewDelhi => Some(4),
+            Agency::Reunion => Some(5),
+            Agency::Bom => Some(6),
+            Agency::Nadi => Some(7),
+            Agency::Wellington => Some(8),
+        }
+    }
+}
+
+impl std::fmt::Display for Agency {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
+impl FromStr for Agency {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "wmo" => Ok(Agency::Wmo),
+            "usa" => Ok(Agency::Usa),
+            "tokyo" => Ok(Agency::Tokyo),
+            "cma" => Ok(Agency::Cma),
+            "hko" => Ok(Agency::Hko),
+            "newdelhi" => Ok(Agency::NewDelhi),
+            "reunion" => Ok(Agency::Reunion),
+            "bom" => Ok(Agency::Bom),
+            "nadi" => Ok(Agency::Nadi),
+            "wellington" => Ok(Agency::Wellington),
+            _ => Err(ValueError::new("agency", s)),
+        }
+    }
+}
+
+// Options controls how IBTrACS rows become track entries.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Options {
+    agency: Agency,
+    interpolated: bool,
+}
+
+impl Options {
+    // with_agency selects the agency whose position, wind and pressure are
+    // used. Rows for which the agency has no wind are left out.
+    pub fn with_agency(mut self, agency: Agency) -> Self {
+        self.agency = agency;
+        self
+    }
+
+    // with_interpolated keeps the rows IBTrACS interpolates between an
+    // agency's reports, which are left out by default.
+    pub fn with_interpolated(mut self, interpolated: bool) -> Self {
+        self.interpolated = interpolated;
+        self
+    }
+
+    pub fn agency(&self) -> Agency {
+        self.agency
+    }
+}
+
+// Track is a storm read from IBTrACS along with its IBTrACS serial id, e.g.
+// 2005236N23285.
+#[derive(Debug)]
+pub struct Track {
+    sid: String,
+    storm: Storm,
+}
+
+impl Track {
+    pub fn sid(&self) -> &str {
+        &self.sid
+    }
+
+    pub fn storm(&self) -> &Storm {
+        &self.storm
+    }
+
+    pub fn into_storm(self) -> Storm {
+        self.storm
+    }
+}
+
+// is_ibtracs returns true if contents begin with the IBTrACS CSV header.
+pub fn is_ibtracs(contents: &[u8]) -> bool {
+    contents.starts_with(b"SID,")
+}
+
+// read reads the storms in an IBTrACS CSV file. The file's first row names
+// the columns and the second gives their units. Storms are given the ATCF id
+// that the US agencies assigned them when there is one. Others, such as
+// those only tracked by another agency, are given the lowest numbers not in
+// use for their basin and season. It is an error if there are more storms
+// than numbers below the invests'.
+pub fn read<R: std::io::Read>(r: R, options: &Options) -> Result<Vec<Track>, Error> {
+    let mut reader = csv::ReaderBuilder::new().flexible(true).from_reader(r);
+    let columns = Columns::new(reader.headers()?, options.agency)?;
+
+    let mut pending: Vec<Pending> = vec![];
+    for (i, record) in reader.records().enumerate() {
+        let record = record?;
+        // The second header row gives the units of each column.
+        if i == 0 && columns.get(&record, columns.sid) == Some("Text") {
+            continue;
+        }
+        let sid = columns.require(&record, columns.sid, "sid")?;
+        if pending.last().map(|p| p.sid.as_str()) != Some(sid) {
+            pending.push(Pending::new(&columns, &record)?);
+        }
+        let storm = pending.last_mut().unwrap();
+        // The US agencies may only start tracking a storm after others have,
+        // so the ATCF id isn't necessarily on the first row.
+        if storm.id.is_none() {
+            storm.id = columns.atcf_id(&record);
+        }
+        if let Some(entry) = columns.entry(&record, options)? {
+            storm.track.push(entry);
+        }
+    }
+
+    let mut used: HashMap<(Basin, i32), BTreeSet<i32>> = HashMap::new();
+    for id in pending.iter().filter_map(|p| p.id.as_ref()) {
+        used.entry((id.basin(), id.year()))
+            .or_default()
+            .insert(id.number());
+    }
+
+    let mut tracks = vec![];
+    for p in pending.into_iter().filter(|p| !p.track.is_empty()) {
+        let id = match p.id {
+            Some(id) => id,
+            None => {
+                let used = used.entry((p.basin, p.season)).or_default();
+                let number = (1..=MAX_NUMBER)
+                    .find(|n| !used.contains(n))
+                    .ok_or_else(|| {
+                        ValueError::new(
+                            "storm number",
+                            &format!(
+                                "none below {} is free for {} in {} {}",
+                                MAX_NUMBER + 1,
+                                p.sid,
+                                p.basin,
+                                p.season
+                            ),
+                        )
+                    })?;
+                used.insert(number);
+                atcf::Id::new(p.basin, number, p.season)
+            }
+        };
+        tracks.push(Track {
+            sid: p.sid,
+            storm: Storm::new(id, Some(p.name), p.track),
+        });
+    }
+    Ok(tracks)
+}
+
+// MAX_NUMBER is the highest number given to a storm without an ATCF id. The
+// numbers above it are used for invests.
+const MAX_NUMBER: i32 = 89;
+
+// Pending is a storm whose rows are still being read.
+struct Pending {
+    sid: String,
+    id: Option<atcf::Id>,
+    basin: Basin,
+    season: i32,
+    name: String,
+    track: Vec<TrackEntry>,
+}
+
+impl Pending {
+    fn new(columns: &Columns, record: &csv::StringRecord) -> Result<Pending, Error> {
+        let basin = columns.require(record, columns.basin, "basin")?;
+        let subbasin = columns.get(record, columns.subbasin);
+        let basin = match (basin, subbasin) {
+            ("NA", _) => Basin::NorthAtlantic,
+            ("EP", Some("CP")) => Basin::CentralNorthPacific,
+            ("EP", _) => Basin::EasternNorthPacific,
+            ("WP", _) => Basin::WesternNorthPacific,
+            ("NI", _) => Basin::NorthIndian,
+            ("SI" | "SP" | "SA", _) => Basin::SouthernHemisphere,
+            (b, _) => return Err(ValueError::new("ibtracs basin", b).into()),
+        };
+        let season = columns.require(record, columns.season, "season")?;
+        let name = match columns.get(record, columns.name) {
+            None | Some("NOT_NAMED") => "UNNAMED",
+            Some(name) => name,
+        };
+        Ok(Pending {
+            sid: columns.require(record, columns.sid, "sid")?.to_owned(),
+            id: columns.atcf_id(record),
+            basin,
+            season: season
+                .parse()
+                .map_err(|_| ValueError::new("ibtracs season", season))?,
+            name: name.to_owned(),
+            track: vec![],
+        })
+    }
+}
+
+// Columns holds the index of each column that is read, which differs between
+// the global file and the per-basin subsets as well as between versions.
+struct Columns {
+    agency: Agency,
+    sid: Option<usize>,
+    season: Option<usize>,
+    basin: Option<usize>,
+    subbasin: Option<usize>,
+    name: Option<usize>,
+    time: Option<usize>,
+    nature: Option<usize>,
+    lat: Option<usize>,
+    lng: Option<usize>,
+    iflag: Option<usize>,
+    atcf_id: Option<usize>,
+    record: Option<usize>,
+    status: Option<usize>,
+    agency_lat: Option<usize>,
+    agency_lng: Option<usize>,
+    wind: Option<usize>,
+    pressure: Option<usize>,
+    radii: [[Option<usize>; 4]; 3],
+    rmw: Option<usize>,
+}
+
+impl Columns {
+    fn new(headers: &csv::StringRecord, agency: Agency) -> Result<Columns, Error> {
+        let find = |name: &str| headers.iter().position(|h| h.trim() == name);
+        let radii = |kts| ["NE", "SE", "SW", "NW"].map(|q| find(&format!("USA_R{}_{}", kts, q)));
+        let columns = Columns {
+            agency,
+            sid: find("SID"),
+            season: find("SEASON"),
+            basin: find("BASIN"),
+            subbasin: find("SUBBASIN"),
+            name: find("NAME"),
+            time: find("ISO_TIME"),
+            nature: find("NATURE"),
+            lat: find("LAT"),
+            lng: find("LON"),
+            iflag: find("IFLAG"),
+            atcf_id: find("USA_ATCF_ID"),
+            record: find("USA_RECORD"),
+            status: find("USA_STATUS"),
+            agency_lat: find(&agency.column("LAT")),
+            agency_lng: find(&agency.column("LON")),
+            wind: find(&agency.column("WIND")),
+            pressure: find(&agency.column("PRES")),
+            radii: [radii(34), radii(50), radii(64)],
+            rmw: find("USA_RMW"),
+        };
+        for (column, name) in [
+            (columns.sid, "SID"),
+            (columns.season, "SEASON"),
+            (columns.basin, "BASIN"),
+            (columns.time, "ISO_TIME"),
+            (columns.lat, "LAT"),
+            (columns.lng, "LON"),
+            (columns.wind, "WIND"),
+        ] {
+            if column.is_none() {
+                return Err(ValueError::new("ibtracs column", name).into());
+            }
+        }
+        Ok(columns)
+    }
+
+    // get returns the trimmed value in column, or None if it is missing or
+    // blank, which is how IBTrACS writes missing values.
+    fn get<'r>(&self, record: &'r csv::StringRecord, column: Option<usize>) -> Option<&'r str> {
+        column
+            .and_then(|i| record.get(i))
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+    }
+
+    fn require<'r>(
+        &self,
+        record: &'r csv::StringRecord,
+        column: Option<usize>,
+        kind: &'static str,
+    ) -> Result<&'r str, ValueError> {
+        self.get(record, column)
+            .ok_or_else(|| ValueError::new(kind, record.as_slice()))
+    }
+
+    fn atcf_id(&self, record: &csv::StringRecord) -> Option<atcf::Id> {
+        self.get(record, self.atcf_id)
+            .and_then(|id| id.parse().ok())
+    }
+
+    fn number<T: FromStr>(
+        &self,
+        record: &csv::StringRecord,
+        column: Option<usize>,
+        kind: &'static str,
+    ) -> Result<Option<T>, ValueError> {
+        self.get(record, column)
+            .map(|v| v.parse().map_err(|_| ValueError::new(kind, v)))
+            .transpose()
+    }
+
+    // entry returns the track entry for a row, or None if the agency has no
+    // wind for it or the row was interpolated and options leave those out.
+    fn entry(
+        &self,
+        record: &csv::StringRecord,
+        options: &Options,
+    ) -> Result<Option<TrackEntry>, Error> {
+        let original = match (self.agency.flag(), self.get(record, self.iflag)) {
+            (Some(i), Some(flags)) => flags.as_bytes().get(i) == Some(&b'O'),
+            (None, Some(flags)) => flags.contains('O'),
+            (_, None) => true,
+        };
+        let wind = match self.number::<f64>(record, self.wind, "ibtracs wind")? {
+            Some(wind) if original || options.interpolated => wind.round() as i32,
+            _ => return Ok(None),
+        };
+
+        let time = self.require(record, self.time, "ibtracs time")?;
+        let time = NaiveDateTime::parse_from_str(time, "%Y-%m-%d %H:%M:%S")
+            .map(|t| DateTime::<Utc>::from_naive_utc_and_offset(t, Utc))
+            .map_err(|_| ValueError::new("ibtracs time", time))?;
+
+        let lat = match self.number(record, self.agency_lat, "ibtracs latitude")? {
+            Some(lat) => lat,
+            None => self
+                .number(record, self.lat, "ibtracs latitude")?
+                .ok_or_else(|| ValueError::new("ibtracs latitude", ""))?,
+        };
+        let lng: f64 = match self.number(record, self.agency_lng, "ibtracs longitude")? {
+            Some(lng) => lng,
+            None => self
+                .number(record, self.lng, "ibtracs longitude")?
+                .ok_or_else(|| ValueError::new("ibtracs longitude", ""))?,
+        };
+        let lng = if lng > 180.0 { lng - 360.0 } else { lng };
+
+        let mut entry = TrackEntry::new(
+            time,
+            self.status(record, wind),
+            geo::Location::new(lat, lng),
+            wind,
+        )
+        .with_indicator(
+            self.get(record, self.record)
+                .and_then(|r| r.chars().next())
+                .and_then(|c| Indicator::from_char(c).ok()),
+        )
+        .with_min_pressure(
+            self.number::<f64>(record, self.pressure, "ibtracs pressure")?
+                .map(|p| p.round() as i32),
+        );
+
+        // Only the US agencies report wind radii in the same 1-minute winds
+        // as their maximum wind.
+        if self.agency == Agency::Usa {
+            for (kts, columns) in [34, 50, 64].into_iter().zip(self.radii) {
+                let [ne, se, sw, nw] = columns
+                    .map(|c| self.number::<f64>(record, c, "ibtracs wind radii"))
+                    .map(|r| r.map(|r| r.map(|r| r.round() as i32)));
+                entry = entry.with_wind_radii(kts, WindRadii::new(ne?, se?, sw?, nw?));
+            }
+            entry = entry.with_radius_of_max_wind(
+                self.number::<f64>(record, self.rmw, "ibtracs rmw")?
+                    .map(|r| r.round() as i32),
+            );
+        }
+        Ok(Some(entry))
+    }
+
+    // status returns the HURDAT2 status of a row. The US agencies report one
+    // directly. Otherwise it is derived from the nature of the storm and the
+    // agency's wind.
+    fn status(&self, record: &csv::StringRecord, wind: i32) -> Status {
+        if self.agency == Agency::Usa {
+            if let Some(status) = self
+                .get(record, self.status)
+                .and_then(|s| s.parse::<Status>().ok())
+            {
+                return status;
+            }
+        }
+        match self.get(record, self.nature) {
+            Some("ET") => Status::Extratropical,
+            Some("DS") => Status::Disturbance,
+            Some("SS") if wind < 34 => Status::SubtropicalDepression,
+            Some("SS") => Status::SubtropicalStorm,
+            _ if wind < 34 => Status::TropicalDepression,
+            _ if wind < 64 => Status::TropicalStorm,
+            _ => Status::Hurricane,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Agency, Options};
+    use crate::{atcf::Basin, hurdat2::Status};
+
+    const SAMPLE: &str = "\
+SID,SEASON,NUMBER,BASIN,SUBBASIN,NAME,ISO_TIME,NATURE,LAT,LON,WMO_WIND,WMO_PRES,IFLAG,USA_ATCF_ID,USA_RECORD,USA_STATUS,USA_LAT,USA_LON,USA_WIND,USA_PRES,USA_R34_NE,USA_R34_SE,USA_R34_SW,USA_R34_NW,TOKYO_LAT,TOKYO_LON,TOKYO_WIND,TOKYO_PRES
+Text,Year, ,Text,Text,Text,Text,Text,degrees_north,degrees_east,kts,mb,Text,Text,Text,Text,degrees_north,degrees_east,kts,mb,nmile,nmile,nmile,nmile,degrees_north,degrees_east,kts,mb
+2005236N23285,2005,69,NA,GM,KATRINA,2005-08-29 06:00:00,TS,28.2,-89.6,110,913,O_____________,AL122005, ,HU,28.2,-89.6,110,913,200,200,150,100, , , ,
+2005236N23285,2005,69,NA,GM,KATRINA,2005-08-29 09:00:00,TS,28.8,-89.6,110,917,I_____________,AL122005, ,HU,28.8,-89.6,110,917,200,200,150,100, , , ,
+2005236N23285,2005,69,NA,GM,KATRINA,2005-08-29 11:10:00,TS,29.3,-89.6,110,920,O_____________,AL122005,L,HU,29.3,-89.6,110,920,200,200,150,100, , , ,
+1959263N12154,1959,61,WP,MM,VERA,1959-09-26 00:00:00,TS,33.8,136.2,, ,_O____________, , , , , , , , , , , ,33.8,136.2,,895
+1959263N12154,1959,61,WP,MM,VERA,1959-09-26 06:00:00,TS,35.2,136.8,, ,OO____________,WP151959, ,TY,35.2,136.8,140,930, , , , ,35.2,136.8,75,930
+1990001S10100,1990,1,SI,WA,NOT_NAMED,1990-01-01 00:00:00,DS,-10.0,100.0,, ,O_____________, , ,DB,-10.0,100.0,20, , , , , , , , ,
+";
+
+    #[test]
+    fn read() {
+        let tracks = super::read(SAMPLE.as_bytes(), &Options::default()).unwrap();
+        let ids = tracks
+            .iter()
+            .map(|t| (t.sid(), t.storm().id().to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ids,
+            vec![
+                ("2005236N23285", "AL122005".to_owned()),
+                ("1959263N12154", "WP151959".to_owned()),
+                ("1990001S10100", "SH011990".to_owned()),
+            ]
+        );
+
+        let katrina = tracks[0].storm();
+        assert_eq!(katrina.track().len(), 2);
+        let landfall = &katrina.track()[1];
+        assert_eq!(landfall.max_sustained_wind(), 110);
+        assert_eq!(landfall.min_pressure(), Some(920));
+        assert_eq!(landfall.status(), Status::Hurricane);
+        assert_eq!(landfall.indicator().map(|i| i.to_str()), Some('L'));
+        assert_eq!(
+            landfall
+                .wind_radii(34)
+                .and_then(|r| r.ne())
+                .map(|d| d.in_nautical_miles().round()),
+            Some(200.0)
+        );
+
+        let vera = tracks[1].storm();
+        assert_eq!(vera.track().len(), 1);
+        assert_eq!(vera.track()[0].status(), Status::Hurricane);
+        assert_eq!(tracks[2].storm().name(), Some("UNNAMED"));
+        assert_eq!(tracks[2].storm().id().basin(), Basin::SouthernHemisphere);
+        assert_eq!(tracks[2].storm().track()[0].status(), Status::Disturbance);
+
+        let options = Options::default()
+            .with_agency("tokyo".parse::<Agency>().unwrap())
+            .with_interpolated(true);
+        let tracks = super::read(SAMPLE.as_bytes(), &options).unwrap();
+        assert_eq!(tracks.len(), 1);
+        let vera = tracks[0].storm();
+        assert_eq!(vera.id().to_string(), "WP151959");
+        assert_eq!(vera.track()[0].max_sustained_wind(), 75);
+        assert_eq!(vera.track()[0].status(), Status::Hurricane);
+        assert_eq!(vera.track()[0].min_pressure(), Some(930));
+    }
+
+    #[test]
+    fn fallback_numbers() {
+        let header = SAMPLE.lines().take(2).collect::<Vec<_>>().join("\n");
+        let season = |n: usize| {
+            let rows = (0..n)
+                .map(|i| {
+                    format!(
+                        "1990{:03}S10100,1990,{},SI,WA,NOT_NAMED,1990-01-01 00:00:00,DS,-10.0,100.0,, ,O_____________, , ,DB,-10.0,100.0,20, , , , , , , , ,",
+                        i, i
+                    )
+                })
+                .collect::<Vec<_>>();
+            format!("{}\n{}\n", header, rows.join("\n"))
+        };
+
+        let tracks = super::read(season(89).as_bytes(), &Options::default()).unwrap();
+        assert_eq!(
+            tracks.last().map(|t| t.storm().id().to_string()).as_deref(),
+            Some("SH891990")
+        );
+        assert!(tracks.iter().all(|t| !t.storm().id().is_invest()));
+        assert!(super::read(season(90).as_bytes(), &Options::default()).is_err());
+    }
+}
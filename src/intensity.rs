@@ -0,0 +1,261 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::{
+    atcf, geo,
+    hurdat2::{Indicator, Storm, TrackPoint},
+};
+
+// Thresholds define what counts as a rapid change in intensity: a change in
+// maximum sustained wind of at least the given number of knots over window.
+#[derive(Debug, Clone, Copy)]
+pub struct Thresholds {
+    intensification: i32,
+    weakening: i32,
+    window: Duration,
+}
+
+impl Thresholds {
+    pub fn new(intensification: i32, weakening: i32, window: Duration) -> Thresholds {
+        Thresholds {
+            intensification,
+            weakening,
+            window,
+        }
+    }
+
+    pub fn intensification(&self) -> i32 {
+        self.intensification
+    }
+
+    pub fn weakening(&self) -> i32 {
+        self.weakening
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+}
+
+// The default thresholds follow the common definition of rapid
+// intensification: an increase of at least 30 kt in 24 hours. Rapid weakening
+// is taken as the mirror image.
+impl Default for Thresholds {
+    fn default() -> Self {
+        Thresholds::new(30, 30, Duration::hours(24))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EventKind {
+    RapidIntensification,
+    RapidWeakening,
+}
+
+impl EventKind {
+    pub fn to_str(&self) -> &str {
+        match self {
+            EventKind::RapidIntensification => "rapid_intensification",
+            EventKind::RapidWeakening => "rapid_weakening",
+        }
+    }
+}
+
+impl std::fmt::Display for EventKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
+// Event is a period of rapid intensification or weakening. Overlapping windows
+// that each exceed the threshold are merged into a single event.
+#[derive(Debug, Clone, Serialize)]
+pub struct Event {
+    storm: atcf::Id,
+    name: Option<String>,
+    kind: EventKind,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    start_location: geo::Location,
+    end_location: geo::Location,
+    delta_wind: f64,
+    delta_pressure: Option<f64>,
+    tagged: bool,
+}
+
+impl Event {
+    pub fn storm(&self) -> &atcf::Id {
+        &self.storm
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn kind(&self) -> EventKind {
+        self.kind
+    }
+
+    pub fn start(&self) -> DateTime<Utc> {
+        self.start
+    }
+
+    pub fn end(&self) -> DateTime<Utc> {
+        self.end
+    }
+
+    pub fn start_location(&self) -> &geo::Location {
+        &self.start_location
+    }
+
+    pub fn end_location(&self) -> &geo::Location {
+        &self.end_location
+    }
+
+    // delta_wind is the change in maximum sustained wind, in knots, from the
+    // start to the end of the event.
+    pub fn delta_wind(&self) -> f64 {
+        self.delta_wind
+    }
+
+    // delta_pressure is the change in minimum central pressure, in millibars,
+    // from the start to the end of the event, if both are known.
+    pub fn delta_pressure(&self) -> Option<f64> {
+        self.delta_pressure
+    }
+
+    // tagged reports whether any track entry within the event carries the
+    // RapidChanges indicator.
+    pub fn tagged(&self) -> bool {
+        self.tagged
+    }
+
+    fn new(storm: &Storm, kind: EventKind, start: &TrackPoint, end: &TrackPoint) -> Event {
+        Event {
+            storm: storm.id().clone(),
+            name: storm.name().map(|n| n.to_owned()),
+            kind,
+            start: start.time(),
+            end: end.time(),
+            start_location: start.location().clone(),
+            end_location: end.location().clone(),
+            delta_wind: end.max_sustained_wind() - start.max_sustained_wind(),
+            delta_pressure: match (start.min_pressure(), end.min_pressure()) {
+                (Some(a), Some(b)) => Some(b - a),
+                _ => None,
+            },
+            tagged: storm.track().iter().any(|e| {
+                matches!(e.indicator(), Some(Indicator::RapidChanges))
+                    && e.time() >= start.time()
+                    && e.time() <= end.time()
+            }),
+        }
+    }
+}
+
+// find_events returns the rapid intensification and weakening events of a
+// storm in chronological order. A window starts at each track entry and the
+// intensity at its end is interpolated from the track.
+pub fn find_events(storm: &Storm, thresholds: &Thresholds) -> Vec<Event> {
+    let mut events = vec![];
+    for kind in [EventKind::RapidIntensification, EventKind::RapidWeakening] {
+        let mut current: Option<(TrackPoint, TrackPoint)> = None;
+        for entry in storm.track() {
+            let start = TrackPoint::from(entry);
+            let end = match storm.position_at(start.time() + thresholds.window) {
+                Some(end) => end,
+                None => break,
+            };
+            let delta = end.max_sustained_wind() - start.max_sustained_wind();
+            let exceeds = match kind {
+                EventKind::RapidIntensification => delta >= thresholds.intensification as f64,
+                EventKind::RapidWeakening => -delta >= thresholds.weakening as f64,
+            };
+            if !exceeds {
+                continue;
+            }
+            current = match current.take() {
+                Some((first, last)) if start.time() <= last.time() => Some((first, end)),
+                Some((first, last)) => {
+                    events.push(Event::new(storm, kind, &first, &last));
+                    Some((start, end))
+                }
+                None => Some((start, end)),
+            };
+        }
+        if let Some((first, last)) = current {
+            events.push(Event::new(storm, kind, &first, &last));
+        }
+    }
+    events.sort_by_key(|e| e.start);
+    events
+}
+
+// untagged_rapid_changes returns the times of track entries that carry the
+// RapidChanges indicator but don't fall within any of the given events.
+pub fn untagged_rapid_changes(storm: &Storm, events: &[Event]) -> Vec<DateTime<Utc>> {
+    storm
+        .track()
+        .iter()
+        .filter(|e| matches!(e.indicator(), Some(Indicator::RapidChanges)))
+        .map(|e| e.time())
+        .filter(|t| !events.iter().any(|ev| *t >= ev.start && *t <= ev.end))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{Duration, TimeZone, Utc};
+
+    use super::{find_events, untagged_rapid_changes, EventKind, Thresholds};
+    use crate::hurdat2::{Hurdat2Reader, ParseMode};
+
+    const SAMPLE: &str = "\
+AL992020,             SAMPLE,      8,
+20200901, 0000,  , TS, 20.0N,  60.0W,  40, 1000, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200901, 0600,  , TS, 20.5N,  61.0W,  45,  998, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200901, 1200,  , HU, 21.0N,  62.0W,  65,  985, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200901, 1800, R, HU, 21.5N,  63.0W,  85,  970, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200902, 0000,  , HU, 22.0N,  64.0W,  90,  965, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200902, 0600,  , HU, 22.5N,  65.0W,  90,  965, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200902, 1200,  , HU, 23.0N,  66.0W,  70,  980, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200903, 0600, R, TS, 24.0N,  68.0W,  55,  990, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+";
+
+    #[test]
+    fn events() {
+        let storm = Hurdat2Reader::new(SAMPLE.as_bytes(), ParseMode::Strict)
+            .next()
+            .unwrap()
+            .unwrap();
+        let t = |d, h| Utc.with_ymd_and_hms(2020, 9, d, h, 0, 0).unwrap();
+
+        let events = find_events(&storm, &Thresholds::default());
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind(), EventKind::RapidIntensification);
+        assert_eq!(events[0].start(), t(1, 0));
+        assert_eq!(events[0].end(), t(2, 6));
+        assert_eq!(events[0].delta_wind(), 50.0);
+        assert_eq!(events[0].delta_pressure(), Some(-35.0));
+        assert!(events[0].tagged());
+        assert_eq!(events[1].kind(), EventKind::RapidWeakening);
+        assert_eq!(events[1].start(), t(2, 0));
+        assert_eq!(events[1].end(), t(3, 6));
+        assert_eq!(events[1].delta_wind(), -35.0);
+        assert!(untagged_rapid_changes(&storm, &events).is_empty());
+
+        let events = find_events(&storm, &Thresholds::new(30, 20, Duration::hours(12)));
+        let kinds = events.iter().map(|e| e.kind()).collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![EventKind::RapidIntensification, EventKind::RapidWeakening]
+        );
+        assert_eq!(events[0].start(), t(1, 6));
+        assert_eq!(events[0].end(), t(1, 18));
+        assert_eq!(events[1].start(), t(2, 0));
+        assert_eq!(events[1].end(), t(2, 18));
+
+        assert_eq!(untagged_rapid_changes(&storm, &events), vec![t(3, 6)]);
+    }
+}
@@ -1,11 +1,34 @@
 pub mod atcf;
+pub mod cache;
+pub mod catalog;
+pub mod climatology;
+pub mod data;
+pub mod diff;
+pub mod diff_revisions;
+pub mod error;
+pub mod export_climatology;
+pub mod export_rapid_changes;
 pub mod export_storms;
+pub mod export_swaths;
+pub mod filter;
+pub mod forecast;
 pub mod geo;
 pub mod hurdat2;
+pub mod ibtracs;
+pub mod intensity;
 pub mod map;
 pub mod noaa;
+pub mod season;
+pub mod seasons;
+pub mod source;
+pub mod swath;
 pub mod update_data;
+pub mod validate;
+pub mod validation;
+pub mod verification;
+pub mod verify;
 
 mod data_dir;
 
-pub use data_dir::{DataDir, FetchStrategy};
+pub use data_dir::{DataDir, FetchError, FetchStrategy, Revision};
+pub use error::{Error, ValueError};
@@ -1,8 +1,9 @@
-use std::error::Error;
-
 use clap::{Parser, Subcommand};
 
-use cat5::{export_storms, update_data, DataDir};
+use cat5::{
+    data, diff_revisions, export_climatology, export_rapid_changes, export_storms, export_swaths,
+    seasons, update_data, validate, verify, DataDir, Error,
+};
 
 #[derive(Debug, Parser)]
 struct Args {
@@ -17,15 +18,31 @@ struct Args {
 enum Command {
     UpdateData(update_data::Args),
     ExportStorms(export_storms::Args),
+    ExportRapidChanges(export_rapid_changes::Args),
+    Seasons(seasons::Args),
+    ExportClimatology(export_climatology::Args),
+    ExportSwaths(export_swaths::Args),
+    Diff(diff_revisions::Args),
+    Data(data::Args),
+    Validate(validate::Args),
+    Verify(verify::Args),
 }
 
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
+async fn main() -> Result<(), Error> {
     let args = Args::parse();
     let client = reqwest::Client::new();
     let data_dir = DataDir::create(&client, &args.data_dir).await?;
     match args.command {
         Command::UpdateData(opts) => update_data::run(&data_dir, opts).await,
-        Command::ExportStorms(args) => export_storms::run(&args).await,
+        Command::ExportStorms(args) => export_storms::run(&data_dir, &args).await,
+        Command::ExportRapidChanges(args) => export_rapid_changes::run(&data_dir, &args).await,
+        Command::Seasons(args) => seasons::run(&data_dir, &args).await,
+        Command::ExportClimatology(args) => export_climatology::run(&data_dir, &args).await,
+        Command::ExportSwaths(args) => export_swaths::run(&data_dir, &args).await,
+        Command::Diff(args) => diff_revisions::run(&data_dir, &args).await,
+        Command::Data(args) => data::run(&data_dir, &args).await,
+        Command::Validate(args) => validate::run(&data_dir, &args).await,
+        Command::Verify(args) => verify::run(&data_dir, &args).await,
     }
 }
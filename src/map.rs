@@ -1,16 +1,23 @@
+use serde::{Deserialize, Serialize};
 use tiny_skia::{ColorU8, Pixmap};
 
 use crate::geo;
-use std::{error::Error, path::Path};
+use std::{io, path::Path};
+use thiserror::Error;
 use tokio::fs;
 
-#[derive(Debug)]
+// Map is the grid of bins that cover the ocean in a map image, along with the
+// projection that places locations on it. update-data writes it to map.json
+// in the data dir when given --map.svg-file.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Map {
+    #[serde(rename = "width")]
     w: usize,
+    #[serde(rename = "height")]
     h: usize,
     bin_size: f64,
     bins: Vec<Bin>,
-    projection: geo::Mercator,
+    projection: geo::MapProjection,
 }
 
 impl Map {
@@ -30,7 +37,7 @@ impl Map {
         &self.bins
     }
 
-    pub fn projection(&self) -> &geo::Mercator {
+    pub fn projection(&self) -> &geo::MapProjection {
         &self.projection
     }
 
@@ -38,9 +45,9 @@ impl Map {
         src: P,
         bin_size: f64,
         land_color: ColorU8,
-        projection: geo::Mercator,
+        projection: geo::MapProjection,
         flood_limit: u32,
-    ) -> Result<Self, Box<dyn Error>> {
+    ) -> Result<Self, MapError> {
         let src = pixmap_from_svg(src).await?;
 
         let mut img = BitImage::from_pixmap(&src, bin_size, land_color);
@@ -66,24 +73,28 @@ impl Map {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Error)]
+pub enum MapError {
+    #[error("unable to read map svg: {0}")]
+    Io(#[from] io::Error),
+
+    #[error("invalid map svg: {0}")]
+    Svg(#[from] usvg::Error),
+
+    #[error("unable to create {width}x{height} pixmap")]
+    Pixmap { width: u32, height: u32 },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Bin {
     pub i: i32,
     pub j: i32,
 }
 
-fn u8_diff(a: u8, b: u8) -> u8 {
-    if a > b {
-        a - b
-    } else {
-        b - a
-    }
-}
-
 fn is_similar_color(a: &ColorU8, b: &ColorU8) -> bool {
-    u8_diff(a.red(), b.red()) < 8
-        && u8_diff(a.green(), b.green()) < 8
-        && u8_diff(a.blue(), b.blue()) < 8
+    a.red().abs_diff(b.red()) < 8
+        && a.green().abs_diff(b.green()) < 8
+        && a.blue().abs_diff(b.blue()) < 8
 }
 
 #[derive(Debug)]
@@ -146,13 +157,13 @@ impl BitImage {
     }
 }
 
-async fn pixmap_from_svg<P: AsRef<Path>>(src: P) -> Result<Pixmap, Box<dyn Error>> {
+async fn pixmap_from_svg<P: AsRef<Path>>(src: P) -> Result<Pixmap, MapError> {
     let opts = usvg::Options::default();
     let data = fs::read(src).await?;
     let tree = usvg::Tree::from_data(&data, &opts)?;
     let size = tree.size();
-    let mut pixels =
-        Pixmap::new(size.width() as u32, size.height() as u32).ok_or("unable to create pixmap")?;
+    let (width, height) = (size.width() as u32, size.height() as u32);
+    let mut pixels = Pixmap::new(width, height).ok_or(MapError::Pixmap { width, height })?;
     resvg::render(&tree, tiny_skia::Transform::default(), &mut pixels.as_mut());
     Ok(pixels)
 }
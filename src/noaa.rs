@@ -1,5 +1,158 @@
-const HURDAT2_URL: &str = "https://www.nhc.noaa.gov/data/hurdat/hurdat2-1851-2023-051124.txt";
+use std::{fmt, str::FromStr};
+
+use crate::{atcf::Basin, ValueError};
+
+// Dataset is a best track file published by NOAA, either a HURDAT2 file from
+// the NHC or the global IBTrACS archive from NCEI. Each is republished with
+// reanalysis changes, so a dataset is identified by both its name and the
+// revision of the file.
+#[derive(Debug, Clone, Copy)]
+pub struct Dataset {
+    name: &'static str,
+    revision: &'static str,
+    basins: &'static [Basin],
+    format: Format,
+    url: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Hurdat2,
+    Ibtracs,
+}
+
+// DATASETS lists the known datasets with the most recent revision of each
+// name last.
+const DATASETS: &[Dataset] = &[
+    Dataset {
+        name: "atlantic",
+        revision: "1851-2023-051124",
+        basins: &[Basin::NorthAtlantic],
+        format: Format::Hurdat2,
+        url: "https://www.nhc.noaa.gov/data/hurdat/hurdat2-1851-2023-051124.txt",
+    },
+    Dataset {
+        name: "pacific",
+        revision: "1949-2023-042624",
+        basins: &[Basin::EasternNorthPacific, Basin::CentralNorthPacific],
+        format: Format::Hurdat2,
+        url: "https://www.nhc.noaa.gov/data/hurdat/hurdat2-nepac-1949-2023-042624.txt",
+    },
+    Dataset {
+        name: "global",
+        revision: "v04r01",
+        basins: &[
+            Basin::NorthAtlantic,
+            Basin::CentralNorthPacific,
+            Basin::EasternNorthPacific,
+            Basin::WesternNorthPacific,
+            Basin::NorthIndian,
+            Basin::SouthernHemisphere,
+        ],
+        format: Format::Ibtracs,
+        url: "https://www.ncei.noaa.gov/data/international-best-track-archive-for-climate-stewardship-ibtracs/v04r01/access/csv/ibtracs.ALL.list.v04r01.csv",
+    },
+];
+
+impl Dataset {
+    pub fn all() -> &'static [Dataset] {
+        DATASETS
+    }
+
+    pub fn atlantic() -> Dataset {
+        Self::latest("atlantic").unwrap()
+    }
+
+    pub fn pacific() -> Dataset {
+        Self::latest("pacific").unwrap()
+    }
+
+    pub fn latest(name: &str) -> Option<Dataset> {
+        DATASETS.iter().rev().find(|d| d.name == name).copied()
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn revision(&self) -> &'static str {
+        self.revision
+    }
+
+    pub fn basins(&self) -> &'static [Basin] {
+        self.basins
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    pub fn url(&self) -> &'static str {
+        self.url
+    }
+
+    // object_name is the name under which the dataset is stored in a DataDir.
+    // Each revision is stored separately, so that asking for one never reads
+    // another that happened to be fetched more recently.
+    pub fn object_name(&self) -> String {
+        match self.format {
+            Format::Hurdat2 => format!("hurdat2-{}-{}.txt", self.name, self.revision),
+            Format::Ibtracs => format!("ibtracs-{}-{}.csv", self.name, self.revision),
+        }
+    }
+}
+
+impl fmt::Display for Dataset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.name, self.revision)
+    }
+}
+
+// Datasets are parsed as either "<name>", which selects the latest revision,
+// or "<name>:<revision>".
+impl FromStr for Dataset {
+    type Err = ValueError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let found = match s.split_once(':') {
+            Some((name, revision)) => DATASETS
+                .iter()
+                .find(|d| d.name == name && d.revision == revision)
+                .copied(),
+            None => Self::latest(s),
+        };
+        found.ok_or_else(|| ValueError::new("dataset", s))
+    }
+}
 
 pub fn hurdat2_url() -> &'static str {
-    HURDAT2_URL
+    Dataset::atlantic().url()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Dataset, Format};
+    use crate::atcf::Basin;
+
+    #[test]
+    fn object_names() {
+        let latest = "atlantic".parse::<Dataset>().unwrap();
+        assert_eq!(
+            latest.object_name(),
+            "hurdat2-atlantic-1851-2023-051124.txt"
+        );
+
+        let older = Dataset {
+            name: "atlantic",
+            revision: "1851-2022-050423",
+            basins: &[Basin::NorthAtlantic],
+            format: Format::Hurdat2,
+            url: "https://www.nhc.noaa.gov/data/hurdat/hurdat2-1851-2022-050423.txt",
+        };
+        assert_ne!(older.object_name(), latest.object_name());
+        assert_eq!(
+            "global".parse::<Dataset>().unwrap().object_name(),
+            "ibtracs-global-v04r01.csv"
+        );
+    }
 }
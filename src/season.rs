@@ -0,0 +1,234 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    atcf,
+    hurdat2::{Status, Storm, TrackEntry},
+};
+
+// Season aggregates the storms of a single basin and year.
+#[derive(Debug, Clone, Serialize)]
+pub struct Season {
+    basin: atcf::Basin,
+    year: i32,
+    storms: usize,
+    named_storms: usize,
+    hurricanes: usize,
+    major_hurricanes: usize,
+    ace: f64,
+    first_formation: Option<DateTime<Utc>>,
+    last_formation: Option<DateTime<Utc>>,
+    strongest: Option<Strongest>,
+}
+
+// Strongest identifies the most intense storm of a season, as measured by
+// maximum sustained wind with minimum pressure breaking ties.
+#[derive(Debug, Clone, Serialize)]
+pub struct Strongest {
+    id: atcf::Id,
+    name: Option<String>,
+    peak_wind: i32,
+    min_pressure: Option<i32>,
+}
+
+impl Strongest {
+    pub fn id(&self) -> &atcf::Id {
+        &self.id
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn peak_wind(&self) -> i32 {
+        self.peak_wind
+    }
+
+    pub fn min_pressure(&self) -> Option<i32> {
+        self.min_pressure
+    }
+}
+
+impl Season {
+    // from_storms groups storms by basin and year, as given by their ATCF
+    // ids, and returns the resulting seasons ordered by year then basin.
+    pub fn from_storms<'a, I>(storms: I) -> Vec<Season>
+    where
+        I: IntoIterator<Item = &'a Storm>,
+    {
+        let mut groups: BTreeMap<(i32, atcf::Basin), Vec<&Storm>> = BTreeMap::new();
+        for storm in storms {
+            groups
+                .entry((storm.id().year(), storm.id().basin()))
+                .or_default()
+                .push(storm);
+        }
+        groups
+            .into_iter()
+            .map(|((year, basin), storms)| Season::new(basin, year, &storms))
+            .collect()
+    }
+
+    fn new(basin: atcf::Basin, year: i32, storms: &[&Storm]) -> Season {
+        let reached = |storm: &Storm, f: fn(&TrackEntry) -> bool| storm.track().iter().any(f);
+        let formations = storms.iter().filter_map(|s| formation(s));
+        Season {
+            basin,
+            year,
+            storms: storms.len(),
+            named_storms: storms.iter().filter(|s| reached(s, is_named)).count(),
+            hurricanes: storms.iter().filter(|s| reached(s, is_hurricane)).count(),
+            major_hurricanes: storms
+                .iter()
+                .filter(|s| reached(s, |e| is_hurricane(e) && e.category().is_major()))
+                .count(),
+            ace: storms.iter().map(|s| s.ace()).sum(),
+            first_formation: formations.clone().min(),
+            last_formation: formations.max(),
+            strongest: storms
+                .iter()
+                .map(|s| (s, s.metrics()))
+                .max_by_key(|(_, m)| (m.peak_wind(), m.min_pressure().map(|p| -p)))
+                .map(|(s, m)| Strongest {
+                    id: s.id().clone(),
+                    name: s.name().map(|n| n.to_owned()),
+                    peak_wind: m.peak_wind(),
+                    min_pressure: m.min_pressure(),
+                }),
+        }
+    }
+
+    pub fn basin(&self) -> atcf::Basin {
+        self.basin
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    // storms is the number of systems of any status in the season.
+    pub fn storms(&self) -> usize {
+        self.storms
+    }
+
+    // named_storms is the number of systems that reached tropical or
+    // subtropical storm strength.
+    pub fn named_storms(&self) -> usize {
+        self.named_storms
+    }
+
+    pub fn hurricanes(&self) -> usize {
+        self.hurricanes
+    }
+
+    pub fn major_hurricanes(&self) -> usize {
+        self.major_hurricanes
+    }
+
+    pub fn ace(&self) -> f64 {
+        self.ace
+    }
+
+    // first_formation is the earliest time any storm of the season first
+    // became a tropical or subtropical cyclone.
+    pub fn first_formation(&self) -> Option<DateTime<Utc>> {
+        self.first_formation
+    }
+
+    // last_formation is the latest time any storm of the season first became
+    // a tropical or subtropical cyclone.
+    pub fn last_formation(&self) -> Option<DateTime<Utc>> {
+        self.last_formation
+    }
+
+    pub fn strongest(&self) -> Option<&Strongest> {
+        self.strongest.as_ref()
+    }
+}
+
+fn is_named(e: &TrackEntry) -> bool {
+    matches!(
+        e.status(),
+        Status::TropicalStorm | Status::Hurricane | Status::SubtropicalStorm
+    ) && e.max_sustained_wind() >= 34
+}
+
+fn is_hurricane(e: &TrackEntry) -> bool {
+    e.status() == Status::Hurricane && e.category().is_hurricane()
+}
+
+// formation returns the time of the first track entry at which the storm was
+// a tropical or subtropical cyclone.
+fn formation(storm: &Storm) -> Option<DateTime<Utc>> {
+    storm
+        .track()
+        .iter()
+        .find(|e| {
+            matches!(
+                e.status(),
+                Status::TropicalDepression
+                    | Status::TropicalStorm
+                    | Status::Hurricane
+                    | Status::SubtropicalDepression
+                    | Status::SubtropicalStorm
+            )
+        })
+        .map(|e| e.time())
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+
+    use super::Season;
+    use crate::{
+        atcf,
+        hurdat2::{Hurdat2Reader, ParseMode},
+    };
+
+    const SAMPLE: &str = "\
+AL012020,             ARTHUR,      3,
+20200516, 1800,  , LO, 28.0N,  78.7W,  25, 1008, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200517, 0000,  , TD, 28.3N,  78.0W,  30, 1008, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200517, 0600,  , TS, 29.0N,  77.3W,  35, 1006, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+AL022020,             BERTHA,      2,
+20200527, 1200,  , HU, 32.0N,  79.0W,  70,  990, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200527, 1800,  , HU, 33.0N,  80.0W, 100,  960, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+AL032021,               ANNA,      1,
+20210601, 0000,  , TD, 20.0N,  60.0W,  30, 1009, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+";
+
+    #[test]
+    fn seasons() {
+        let storms = Hurdat2Reader::new(SAMPLE.as_bytes(), ParseMode::Strict)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let seasons = Season::from_storms(&storms);
+        assert_eq!(seasons.len(), 2);
+
+        let s = &seasons[0];
+        assert_eq!(s.basin(), atcf::Basin::NorthAtlantic);
+        assert_eq!(s.year(), 2020);
+        assert_eq!(s.storms(), 2);
+        assert_eq!(s.named_storms(), 2);
+        assert_eq!(s.hurricanes(), 1);
+        assert_eq!(s.major_hurricanes(), 1);
+        assert!((s.ace() - (0.1225 + 0.49 + 1.0)).abs() < 1e-9);
+        assert_eq!(
+            s.first_formation(),
+            Some(Utc.with_ymd_and_hms(2020, 5, 17, 0, 0, 0).unwrap())
+        );
+        assert_eq!(
+            s.last_formation(),
+            Some(Utc.with_ymd_and_hms(2020, 5, 27, 12, 0, 0).unwrap())
+        );
+        assert_eq!(s.strongest().unwrap().name(), Some("BERTHA"));
+
+        let s = &seasons[1];
+        assert_eq!(s.year(), 2021);
+        assert_eq!(s.named_storms(), 0);
+        assert_eq!(s.ace(), 0.0);
+    }
+}
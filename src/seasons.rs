@@ -0,0 +1,145 @@
+use std::io::Write;
+
+use tokio::fs;
+
+use crate::{filter::Filter, season::Season, source::StormSource, DataDir, Error};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[clap(flatten)]
+    source: StormSource,
+
+    #[clap(
+        long = "filter",
+        default_value = "all",
+        help = "only include storms matching the filter expression, may be repeated"
+    )]
+    filters: Vec<Filter>,
+
+    #[clap(long, value_enum, default_value_t = Format::Table, help = "output format")]
+    format: Format,
+
+    #[clap(long, help = "path to write the output to instead of stdout")]
+    dst: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+pub async fn run(dir: &DataDir<'_>, args: &Args) -> Result<(), Error> {
+    let storms = args
+        .source
+        .load(dir, |s| args.filters.iter().all(|f| f.matches(s)))
+        .await?;
+    let seasons = Season::from_storms(&storms);
+
+    let contents = match args.format {
+        Format::Table => to_table(&seasons)?,
+        Format::Json => serde_json::to_vec(&seasons)?,
+        Format::Csv => to_csv(&seasons)?,
+    };
+
+    match &args.dst {
+        Some(dst) => fs::write(dst, contents).await?,
+        None => std::io::stdout().write_all(&contents)?,
+    }
+
+    Ok(())
+}
+
+fn to_table(seasons: &[Season]) -> Result<Vec<u8>, Error> {
+    let mut w = Vec::new();
+    writeln!(
+        w,
+        "{:<5} {:>4} {:>6} {:>5} {:>5} {:>5} {:>7}  {:<11} {:<11} STRONGEST",
+        "BASIN", "YEAR", "STORMS", "NAMED", "HURR", "MAJOR", "ACE", "FIRST", "LAST"
+    )?;
+    for season in seasons {
+        let date = |t: Option<chrono::DateTime<chrono::Utc>>| {
+            t.map(|t| t.format("%b %d").to_string())
+                .unwrap_or_else(|| "-".to_owned())
+        };
+        let strongest = season
+            .strongest()
+            .map(|s| {
+                format!(
+                    "{} {} ({} kt)",
+                    s.id(),
+                    s.name().unwrap_or("UNNAMED"),
+                    s.peak_wind()
+                )
+            })
+            .unwrap_or_else(|| "-".to_owned());
+        writeln!(
+            w,
+            "{:<5} {:>4} {:>6} {:>5} {:>5} {:>5} {:>7.1}  {:<11} {:<11} {}",
+            season.basin().to_str(),
+            season.year(),
+            season.storms(),
+            season.named_storms(),
+            season.hurricanes(),
+            season.major_hurricanes(),
+            season.ace(),
+            date(season.first_formation()),
+            date(season.last_formation()),
+            strongest
+        )?;
+    }
+    Ok(w)
+}
+
+fn to_csv(seasons: &[Season]) -> Result<Vec<u8>, Error> {
+    let mut w = csv::Writer::from_writer(Vec::new());
+    w.write_record([
+        "basin",
+        "year",
+        "storms",
+        "named_storms",
+        "hurricanes",
+        "major_hurricanes",
+        "ace",
+        "first_formation",
+        "last_formation",
+        "strongest_id",
+        "strongest_name",
+        "strongest_peak_wind",
+        "strongest_min_pressure",
+    ])?;
+    for season in seasons {
+        let strongest = season.strongest();
+        w.write_record([
+            season.basin().to_string(),
+            season.year().to_string(),
+            season.storms().to_string(),
+            season.named_storms().to_string(),
+            season.hurricanes().to_string(),
+            season.major_hurricanes().to_string(),
+            season.ace().to_string(),
+            season
+                .first_formation()
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default(),
+            season
+                .last_formation()
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default(),
+            strongest.map(|s| s.id().to_string()).unwrap_or_default(),
+            strongest
+                .and_then(|s| s.name())
+                .unwrap_or_default()
+                .to_owned(),
+            strongest
+                .map(|s| s.peak_wind().to_string())
+                .unwrap_or_default(),
+            strongest
+                .and_then(|s| s.min_pressure())
+                .map(|p| p.to_string())
+                .unwrap_or_default(),
+        ])?;
+    }
+    w.into_inner().map_err(|e| Error::Io(e.into_error()))
+}
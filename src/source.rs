@@ -0,0 +1,228 @@
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use tokio::{fs, io::AsyncReadExt};
+
+use crate::{
+    atcf, cache,
+    hurdat2::{ParseMode, Storm},
+    ibtracs, noaa, DataDir, Error, ValueError,
+};
+
+// StormSource holds the command line options that select where a command
+// reads its storms from: either datasets previously fetched into the DataDir
+// by update-data or a HURDAT2, IBTrACS or ATCF b-deck file given explicitly.
+#[derive(Debug, Clone, clap::Args)]
+pub struct StormSource {
+    #[clap(
+        long = "dataset",
+        help = "dataset to read, as fetched by update-data, may be repeated [default: atlantic]"
+    )]
+    datasets: Vec<noaa::Dataset>,
+
+    #[clap(
+        long,
+        conflicts_with = "datasets",
+        help = "hurdat2, IBTrACS or ATCF b-deck file to read instead of a dataset"
+    )]
+    src: Option<String>,
+
+    #[clap(
+        long,
+        default_value_t = ibtracs::Agency::default(),
+        help = "agency whose positions and winds are read from IBTrACS data"
+    )]
+    agency: ibtracs::Agency,
+
+    #[clap(
+        long,
+        value_parser = parse_as_of,
+        conflicts_with = "src",
+        help = "read the revision of each dataset that was current at this date or time"
+    )]
+    as_of: Option<DateTime<Utc>>,
+
+    #[clap(
+        long,
+        help = "fail on malformed HURDAT2 records instead of repairing or skipping them"
+    )]
+    strict: bool,
+}
+
+impl StormSource {
+    pub async fn load<F>(&self, dir: &DataDir<'_>, filter: F) -> Result<Vec<Storm>, Error>
+    where
+        F: Fn(&Storm) -> bool,
+    {
+        let mut storms = vec![];
+        for src in self.paths(dir).await? {
+            storms.extend(load_path(dir, src, self.agency, self.mode(), &filter).await?);
+        }
+        Ok(storms)
+    }
+
+    pub fn agency(&self) -> ibtracs::Agency {
+        self.agency
+    }
+
+    // mode returns how HURDAT2 files are parsed, which is leniently unless
+    // --strict was given.
+    pub fn mode(&self) -> ParseMode {
+        if self.strict {
+            ParseMode::Strict
+        } else {
+            ParseMode::Lenient
+        }
+    }
+
+    // is_default reports whether none of --dataset, --src or --as-of were
+    // given, so storms are read from the latest atlantic dataset.
+    pub fn is_default(&self) -> bool {
+        self.datasets.is_empty() && self.src.is_none() && self.as_of.is_none()
+    }
+
+    // with_src returns a copy of the source that reads from the file at src.
+    pub fn with_src(&self, src: &str) -> StormSource {
+        StormSource {
+            src: Some(src.to_owned()),
+            ..self.clone()
+        }
+    }
+
+    // paths returns the paths of the files that storms are read from.
+    pub async fn paths(&self, dir: &DataDir<'_>) -> Result<Vec<PathBuf>, Error> {
+        let mut srcs = vec![];
+        match &self.src {
+            Some(src) => srcs.push(src.into()),
+            None => {
+                let datasets = match &self.datasets[..] {
+                    [] => vec![noaa::Dataset::atlantic()],
+                    datasets => datasets.to_vec(),
+                };
+                for dataset in &datasets {
+                    let object = dir.get_object(&dataset.object_name());
+                    let path = match self.as_of {
+                        Some(t) => match object.revision_at(t).await? {
+                            Some(revision) => object.revision_path(&revision),
+                            None => {
+                                return Err(Error::Usage(format!(
+                                    "no revision of {} was fetched by {}",
+                                    dataset.name(),
+                                    t
+                                )))
+                            }
+                        },
+                        None => object.path(),
+                    };
+                    srcs.push(path);
+                }
+            }
+        }
+        Ok(srcs)
+    }
+}
+
+// parse_as_of accepts either an RFC 3339 time or a date, which is taken to
+// mean the end of that day.
+fn parse_as_of(s: &str) -> Result<DateTime<Utc>, ValueError> {
+    if let Ok(t) = DateTime::parse_from_rfc3339(s) {
+        return Ok(t.with_timezone(&Utc));
+    }
+    NaiveDate::parse_from_str(s, "%Y-%m-%d")
+        .ok()
+        .and_then(|d| d.succ_opt())
+        .and_then(|d| d.and_hms_opt(0, 0, 0))
+        .map(|t| t.and_utc() - chrono::Duration::nanoseconds(1))
+        .ok_or_else(|| ValueError::new("time", s))
+}
+
+// load_path reads the storms in the HURDAT2, IBTrACS or b-deck file at path
+// that match filter. HURDAT2 and IBTrACS files are read from the parsed copy
+// cached in dir when there is one. HURDAT2 files are parsed in the given mode,
+// with any diagnostics printed to stderr. IBTrACS files are read using the
+// given agency's positions and winds.
+pub async fn load_path<P, F>(
+    dir: &DataDir<'_>,
+    path: P,
+    agency: ibtracs::Agency,
+    mode: ParseMode,
+    filter: F,
+) -> Result<Vec<Storm>, Error>
+where
+    P: AsRef<Path>,
+    F: Fn(&Storm) -> bool,
+{
+    let path = path.as_ref();
+    let mut storms = match Format::of_path(path).await? {
+        Format::Hurdat2 => {
+            let (storms, diagnostics) = cache::load(dir, path, mode).await?;
+            for diagnostic in &diagnostics {
+                eprintln!("{}: {}", path.display(), diagnostic);
+            }
+            storms
+        }
+        Format::Ibtracs => cache::load_ibtracs(dir, path, agency).await?,
+        Format::BDeck => {
+            let contents = fs::read(path).await?;
+            vec![atcf::BestTrack::read(contents.as_slice())?.to_storm()]
+        }
+    };
+    storms.retain(filter);
+    Ok(storms)
+}
+
+// Format is the format of a file that storms are read from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Hurdat2,
+    Ibtracs,
+    BDeck,
+}
+
+impl Format {
+    // of_path determines the format of the file at path from its first few
+    // bytes.
+    pub async fn of_path<P: AsRef<Path>>(path: P) -> Result<Format, Error> {
+        let mut head = vec![];
+        fs::File::open(path)
+            .await?
+            .take(4)
+            .read_to_end(&mut head)
+            .await?;
+        if ibtracs::is_ibtracs(&head) {
+            Ok(Format::Ibtracs)
+        } else if atcf::is_deck(&head) {
+            Ok(Format::BDeck)
+        } else {
+            Ok(Format::Hurdat2)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+
+    use super::parse_as_of;
+
+    #[test]
+    fn as_of() {
+        assert_eq!(
+            parse_as_of("2024-05-01T12:00:00Z").unwrap(),
+            Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+        );
+        assert_eq!(
+            parse_as_of("2024-05-01T08:00:00-04:00").unwrap(),
+            Utc.with_ymd_and_hms(2024, 5, 1, 12, 0, 0).unwrap()
+        );
+
+        // A date covers the whole day, so it includes revisions fetched on it.
+        let t = parse_as_of("2024-05-01").unwrap();
+        assert!(t > Utc.with_ymd_and_hms(2024, 5, 1, 23, 59, 59).unwrap());
+        assert!(t < Utc.with_ymd_and_hms(2024, 5, 2, 0, 0, 0).unwrap());
+
+        assert!(parse_as_of("2024-13-01").is_err());
+        assert!(parse_as_of("yesterday").is_err());
+    }
+}
@@ -0,0 +1,334 @@
+use chrono::Duration;
+use i_overlay::{core::fill_rule::FillRule, float::simplify::SimplifyShape};
+use serde_json::json;
+
+use crate::{
+    geo::{self, Projection},
+    hurdat2::{Storm, TrackEntry},
+    map::{Bin, Map},
+};
+
+// ARC_STEPS is the number of segments used to approximate each quadrant's arc.
+const ARC_STEPS: usize = 8;
+
+// Swath is the area swept by a storm's wind field over its lifetime for a
+// single wind speed threshold. It is a set of polygons, each an outer ring
+// followed by any holes, in longitude and latitude. Rings are not closed and
+// longitudes are kept continuous along the track, so a swath that crosses the
+// antimeridian may have longitudes outside of [-180, 180].
+#[derive(Debug, Clone)]
+pub struct Swath {
+    wind: i32,
+    polygons: Vec<Vec<Vec<geo::Location>>>,
+}
+
+impl Swath {
+    // from_storm builds the swath for winds of at least kts by sweeping the
+    // quadrant shapes of the wind radii along the track. Radii are
+    // interpolated linearly between track entries at the given interval and
+    // each quadrant's sector is swept by taking the convex hull of it at
+    // consecutive samples. Segments where either entry has no radii are left
+    // out, which is all of the track before 2004.
+    pub fn from_storm(storm: &Storm, kts: i32, interval: Duration) -> Swath {
+        let track = storm.track();
+        let reference = track.first().map_or(0.0, |e| e.location().lng());
+
+        let mut contours: Vec<Vec<[f64; 2]>> = vec![];
+        let mut sweep = |a: &Sample, b: &Sample| {
+            for q in 0..4 {
+                if a.radii[q] <= 0.0 && b.radii[q] <= 0.0 {
+                    continue;
+                }
+                let mut pts = a.sector(q, reference);
+                pts.extend(b.sector(q, reference));
+                contours.push(convex_hull(pts));
+            }
+        };
+
+        let samples = track
+            .iter()
+            .filter_map(|e| Sample::from_entry(e, kts))
+            .collect::<Vec<_>>();
+        if let [only] = samples.as_slice() {
+            sweep(only, only);
+        }
+
+        for w in track.windows(2) {
+            let (a, b) = match (
+                Sample::from_entry(&w[0], kts),
+                Sample::from_entry(&w[1], kts),
+            ) {
+                (Some(a), Some(b)) => (a, b),
+                _ => continue,
+            };
+            let secs = (w[1].time() - w[0].time()).num_seconds() as f64;
+            let step = interval.num_seconds().max(1) as f64;
+            let n = (secs / step).ceil().max(1.0) as usize;
+            let mut prev = a.clone();
+            for k in 1..=n {
+                let next = Sample::between(&a, &b, k as f64 / n as f64);
+                sweep(&prev, &next);
+                prev = next;
+            }
+        }
+
+        let polygons = contours
+            .simplify_shape(FillRule::NonZero)
+            .into_iter()
+            .map(|shape| {
+                shape
+                    .into_iter()
+                    .map(|ring| {
+                        ring.into_iter()
+                            .map(|[lng, lat]| geo::Location::new(lat, lng))
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Swath {
+            wind: kts,
+            polygons,
+        }
+    }
+
+    pub fn wind(&self) -> i32 {
+        self.wind
+    }
+
+    pub fn polygons(&self) -> &[Vec<Vec<geo::Location>>] {
+        &self.polygons
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.polygons.is_empty()
+    }
+
+    // contains reports whether loc is within the swath.
+    pub fn contains(&self, loc: &geo::Location) -> bool {
+        [0.0, 360.0, -360.0].iter().any(|off| {
+            let pt = [loc.lng() + off, loc.lat()];
+            self.polygons.iter().any(|polygon| {
+                polygon
+                    .iter()
+                    .filter(|ring| ring_contains(ring, pt))
+                    .count()
+                    % 2
+                    == 1
+            })
+        })
+    }
+
+    // bins returns the bins of the map whose centers fall within the swath.
+    pub fn bins<'a>(&self, map: &'a Map) -> Vec<&'a Bin> {
+        let size = map.bin_size();
+        map.bins()
+            .iter()
+            .filter(|b| {
+                let pt = ((b.i as f64 + 0.5) * size, (b.j as f64 + 0.5) * size);
+                self.contains(&map.projection().pt_to_location(pt))
+            })
+            .collect()
+    }
+
+    // to_geojson returns the swath as a GeoJSON MultiPolygon geometry.
+    pub fn to_geojson(&self) -> serde_json::Value {
+        let coordinates = self
+            .polygons
+            .iter()
+            .map(|polygon| {
+                polygon
+                    .iter()
+                    .map(|ring| {
+                        ring.iter()
+                            .chain(ring.first())
+                            .map(|l| [l.lng(), l.lat()])
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "type": "MultiPolygon",
+            "coordinates": coordinates,
+        })
+    }
+}
+
+// Sample is a position along the track with the radii, in nautical miles, of
+// the NE, SE, SW and NW quadrants.
+#[derive(Debug, Clone)]
+struct Sample {
+    location: geo::Location,
+    radii: [f64; 4],
+}
+
+impl Sample {
+    fn from_entry(e: &TrackEntry, kts: i32) -> Option<Sample> {
+        let r = e.wind_radii(kts)?;
+        let quadrants = [r.ne(), r.se(), r.sw(), r.nw()];
+        if quadrants.iter().all(|q| q.is_none()) {
+            return None;
+        }
+        Some(Sample {
+            location: e.location().clone(),
+            radii: quadrants.map(|q| q.map_or(0.0, |d| d.in_nautical_miles())),
+        })
+    }
+
+    fn between(a: &Sample, b: &Sample, f: f64) -> Sample {
+        let mut radii = [0.0; 4];
+        for (q, r) in radii.iter_mut().enumerate() {
+            *r = a.radii[q] + (b.radii[q] - a.radii[q]) * f;
+        }
+        Sample {
+            location: geo::Location::intermediate_point(&a.location, &b.location, f),
+            radii,
+        }
+    }
+
+    // sector returns the points of the quadrant's sector, the center followed
+    // by its arc, as [lng, lat] with longitudes unwrapped around reference.
+    fn sector(&self, q: usize, reference: f64) -> Vec<[f64; 2]> {
+        let mut pts = vec![unwrap_lng(&self.location, reference)];
+        if self.radii[q] > 0.0 {
+            let distance = geo::Distance::from_nautical_miles(self.radii[q]);
+            for i in 0..=ARC_STEPS {
+                let deg = 90.0 * q as f64 + 90.0 * i as f64 / ARC_STEPS as f64;
+                let loc = self
+                    .location
+                    .destination_of(geo::Bearing::from_degrees(deg), distance);
+                pts.push(unwrap_lng(&loc, reference));
+            }
+        }
+        pts
+    }
+}
+
+fn unwrap_lng(loc: &geo::Location, reference: f64) -> [f64; 2] {
+    let lng = reference + (loc.lng() - reference + 540.0).rem_euclid(360.0) - 180.0;
+    [lng, loc.lat()]
+}
+
+// convex_hull returns the counter-clockwise convex hull of pts using the
+// monotone chain algorithm.
+fn convex_hull(mut pts: Vec<[f64; 2]>) -> Vec<[f64; 2]> {
+    pts.sort_by(|a, b| a[0].total_cmp(&b[0]).then(a[1].total_cmp(&b[1])));
+    pts.dedup();
+    if pts.len() < 3 {
+        return pts;
+    }
+    let cross = |o: [f64; 2], a: [f64; 2], b: [f64; 2]| {
+        (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+    };
+    let mut hull: Vec<[f64; 2]> = vec![];
+    for pass in [pts.clone(), pts.into_iter().rev().collect()] {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2
+                && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0
+            {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        hull.pop();
+    }
+    hull
+}
+
+// ring_contains reports whether pt is inside ring using the even-odd rule.
+fn ring_contains(ring: &[geo::Location], pt: [f64; 2]) -> bool {
+    let mut inside = false;
+    for (i, a) in ring.iter().enumerate() {
+        let b = &ring[(i + 1) % ring.len()];
+        let (ax, ay, bx, by) = (a.lng(), a.lat(), b.lng(), b.lat());
+        if (ay > pt[1]) != (by > pt[1]) && pt[0] < ax + (pt[1] - ay) * (bx - ax) / (by - ay) {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::Duration;
+    use serde_json::json;
+
+    use crate::{
+        geo,
+        hurdat2::{Hurdat2Reader, ParseMode},
+        map::Map,
+    };
+
+    const SAMPLE: &str = "\
+AL992020,             SAMPLE,      2,
+20200901, 0000,  , HU, 20.0N,  60.0W,  70,  990,   60,   60,   60,   60,   30,   30,   30,   30,    0,    0,    0,    0, -999,
+20200901, 1200,  , HU, 20.0N,  62.0W,  70,  990,   60,   60,   60,   60,   30,   30,   30,   30,   20,    0,    0,    0, -999,
+";
+
+    #[test]
+    fn swath() {
+        let storm = Hurdat2Reader::new(SAMPLE.as_bytes(), ParseMode::Strict)
+            .next()
+            .unwrap()
+            .unwrap();
+
+        let swath = storm.wind_swath(34, Duration::hours(1));
+        assert_eq!(swath.wind(), 34);
+        assert_eq!(swath.polygons().len(), 1);
+        assert!(swath.contains(&geo::Location::new(20.0, -61.0)));
+        assert!(swath.contains(&geo::Location::new(20.9, -61.0)));
+        assert!(swath.contains(&geo::Location::new(19.1, -60.0)));
+        assert!(!swath.contains(&geo::Location::new(21.1, -61.0)));
+        assert!(!swath.contains(&geo::Location::new(20.0, -63.2)));
+
+        let swath = storm.wind_swath(64, Duration::hours(1));
+        assert_eq!(swath.polygons().len(), 1);
+        assert!(swath.contains(&geo::Location::new(20.1, -61.9)));
+        assert!(!swath.contains(&geo::Location::new(20.1, -60.1)));
+        assert!(!swath.contains(&geo::Location::new(19.9, -61.9)));
+
+        let geojson = swath.to_geojson();
+        assert_eq!(geojson["type"], "MultiPolygon");
+    }
+
+    #[test]
+    fn bins() {
+        let storm = Hurdat2Reader::new(SAMPLE.as_bytes(), ParseMode::Strict)
+            .next()
+            .unwrap()
+            .unwrap();
+
+        // A map of one degree bins, whose centers are at half degrees, over
+        // the track.
+        let map: Map = serde_json::from_value(json!({
+            "width": 360,
+            "height": 180,
+            "bin_size": 1.0,
+            "bins": [
+                {"i": 118, "j": 69},
+                {"i": 118, "j": 67},
+                {"i": 114, "j": 69},
+                {"i": 120, "j": 70},
+            ],
+            "projection": {
+                "type": "equirectangular",
+                "width": 360.0,
+                "height": 180.0,
+                "xoff": 0.0,
+                "yoff": 0.0,
+            },
+        }))
+        .unwrap();
+
+        let swath = storm.wind_swath(34, Duration::hours(1));
+        let bins = swath
+            .bins(&map)
+            .iter()
+            .map(|b| (b.i, b.j))
+            .collect::<Vec<_>>();
+        assert_eq!(bins, vec![(118, 69), (120, 70)]);
+    }
+}
@@ -1,19 +1,29 @@
-use std::error::Error;
-
-use csv_async::{AsyncReaderBuilder, StringRecordsStream};
 use tiny_skia::ColorU8;
-use tokio::io;
+use tokio::fs;
 
 use crate::{
-    geo::{self, Mercator},
-    hurdat2::{Status, Storm},
-    noaa, DataDir, FetchStrategy,
+    cache,
+    geo::{self, MapProjection},
+    hurdat2::{ParseMode, Status},
+    ibtracs,
+    map::Map,
+    noaa, source, DataDir, Error, FetchStrategy,
 };
 
 #[derive(Debug, clap::Args)]
 pub struct Args {
-    #[clap(long, default_value_t = noaa::hurdat2_url().to_owned())]
-    hurdat2_url: String,
+    #[clap(
+        long = "dataset",
+        default_value = "atlantic",
+        help = "dataset to fetch, either <name> or <name>:<revision>"
+    )]
+    datasets: Vec<noaa::Dataset>,
+
+    #[clap(long, help = "fetch the dataset from this URL instead")]
+    hurdat2_url: Option<String>,
+
+    #[clap(long, help = "fail on the first invalid record instead of skipping it")]
+    strict: bool,
 
     #[clap(flatten)]
     map: ForMap,
@@ -21,8 +31,12 @@ pub struct Args {
 
 #[derive(Debug, clap::Args)]
 struct ForMap {
-    #[clap(long = "map.svg-file", default_value_t=String::from("atlantic.svg"))]
-    svg_file: String,
+    #[clap(
+        long = "map.svg-file",
+        requires = "projection",
+        help = "svg of the map to rasterize into map.json; the map is left alone if not given"
+    )]
+    svg_file: Option<String>,
 
     #[clap(long = "map.land-color", value_parser=parse_color, default_value="#facbc0")]
     land_color: ColorU8,
@@ -30,8 +44,19 @@ struct ForMap {
     #[clap(long = "map.bin-size", default_value_t = 10.0)]
     bin_size: f64,
 
-    #[clap(long = "map.projection", value_parser=parse_projection,)]
-    projection: Mercator,
+    #[clap(
+        long = "map.projection",
+        value_parser=parse_projection,
+        help = "projection of the map svg as <name>:[<params>], where name is mercator, equirectangular, lcc, albers or orthographic; required with --map.svg-file"
+    )]
+    projection: Option<MapProjection>,
+
+    #[clap(
+        long = "map.flood-limit",
+        default_value_t = 10,
+        help = "bins of land in groups smaller than this are treated as water"
+    )]
+    flood_limit: u32,
 }
 
 fn parse_color(s: &str) -> Result<ColorU8, String> {
@@ -48,57 +73,73 @@ fn parse_color(s: &str) -> Result<ColorU8, String> {
     ))
 }
 
-fn parse_projection(s: &str) -> Result<geo::Mercator, String> {
+fn parse_projection(s: &str) -> Result<geo::MapProjection, String> {
     s.parse().map_err(|_| format!("invalid projection: {}", s))
 }
 
-fn default_projection() -> geo::Mercator {
-    geo::Mercator::new(
-        10368.61626248217,
-        10310.9627199,
-        -2160.1283880171186,
-        -3566.7693291,
-    )
-}
-async fn collect_storms<'a, R, F>(
-    stream: &mut StringRecordsStream<'a, R>,
-    filter: F,
-) -> Result<Vec<Storm>, Box<dyn Error>>
-where
-    R: io::AsyncRead + Unpin + Send,
-    F: Fn(&Storm) -> bool,
-{
-    let mut storms = vec![];
-    while let Some(storm) = Storm::from_record_stream(stream).await {
-        let storm = storm?;
-        if filter(&storm) {
-            storms.push(storm);
-        }
+pub async fn run(dir: &DataDir<'_>, args: Args) -> Result<(), Error> {
+    println!("{:?}", args);
+    if args.hurdat2_url.is_some() && args.datasets.len() != 1 {
+        return Err(Error::Usage(
+            "--hurdat2-url can only be used with a single dataset".into(),
+        ));
     }
-    Ok(storms)
-}
 
-pub async fn run(dir: &DataDir<'_>, args: Args) -> Result<(), Box<dyn Error>> {
-    println!("{:?}", args);
-    let f = dir
-        .get_object("hurdat2.txt")
-        .fetch(&args.hurdat2_url, FetchStrategy::Always)
-        .await?
-        .open()
+    // The map is only rebuilt when asked for, so that fetching datasets
+    // doesn't depend on having the svg at hand.
+    if let Some(svg_file) = &args.map.svg_file {
+        let projection = args.map.projection.clone().ok_or_else(|| {
+            Error::Usage("--map.projection is required with --map.svg-file".into())
+        })?;
+        let map = Map::build(
+            svg_file,
+            args.map.bin_size,
+            args.map.land_color,
+            projection,
+            args.map.flood_limit,
+        )
         .await?;
-    let mut stream = AsyncReaderBuilder::new()
-        .flexible(true)
-        .has_headers(false)
-        .create_reader(f);
-    let mut records = stream.records();
-    let storms = collect_storms(&mut records, |storm| {
-        storm
-            .track()
+        fs::write(dir.path().join("map.json"), serde_json::to_vec(&map)?).await?;
+    }
+
+    let mode = if args.strict {
+        ParseMode::Strict
+    } else {
+        ParseMode::Lenient
+    };
+
+    for dataset in &args.datasets {
+        let url = args.hurdat2_url.as_deref().unwrap_or(dataset.url());
+        let object = dir
+            .get_object(&dataset.object_name())
+            .fetch(url, FetchStrategy::Always)
+            .await?;
+        let storms = match dataset.format() {
+            noaa::Format::Hurdat2 => {
+                let (storms, diagnostics) = cache::load(dir, object.path(), mode).await?;
+                for diagnostic in &diagnostics {
+                    eprintln!("{}: {}", dataset, diagnostic);
+                }
+                storms
+            }
+            noaa::Format::Ibtracs => {
+                source::load_path(dir, object.path(), ibtracs::Agency::default(), mode, |_| {
+                    true
+                })
+                .await?
+            }
+        };
+        let storms = storms
             .iter()
-            .any(|e| e.status() == Status::Hurricane)
-    })
-    .await?;
-    println!("{} hurricanes found", storms.len());
+            .filter(|storm| {
+                storm
+                    .track()
+                    .iter()
+                    .any(|e| e.status() == Status::Hurricane)
+            })
+            .collect::<Vec<_>>();
+        println!("{}: {} hurricanes found", dataset, storms.len());
+    }
 
     Ok(())
 }
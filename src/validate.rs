@@ -0,0 +1,88 @@
+use std::io::Write;
+
+use tokio::fs;
+use tokio_stream::StreamExt;
+
+use crate::{
+    hurdat2::{Hurdat2Stream, ParseMode},
+    source::{self, StormSource},
+    validation::{Finding, Severity, Validator},
+    DataDir, Error,
+};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[clap(flatten)]
+    source: StormSource,
+
+    #[clap(long, value_enum, default_value_t = Format::Text, help = "output format")]
+    format: Format,
+
+    #[clap(
+        long,
+        default_value_t = 70.0,
+        help = "report motion between fixes faster than this as a jump (kt)"
+    )]
+    max_speed: f64,
+
+    #[clap(long, help = "only report errors, not warnings")]
+    errors_only: bool,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum Format {
+    Text,
+    Json,
+}
+
+pub async fn run(dir: &DataDir<'_>, args: &Args) -> Result<(), Error> {
+    let validator = Validator::default().with_max_speed(args.max_speed);
+
+    // Files are parsed leniently so that problems with the records themselves
+    // are reported along with everything else rather than ending the run.
+    let mut findings = vec![];
+    for path in args.source.paths(dir).await? {
+        if source::Format::of_path(&path).await? != source::Format::Hurdat2 {
+            for storm in
+                source::load_path(dir, &path, args.source.agency(), ParseMode::Lenient, |_| {
+                    true
+                })
+                .await?
+            {
+                findings.extend(validator.validate(&storm));
+            }
+            continue;
+        }
+        let r = fs::File::open(&path).await?;
+        let mut stream = Hurdat2Stream::from_reader(r, ParseMode::Lenient);
+        while let Some(storm) = stream.next().await {
+            findings.extend(validator.validate(&storm?));
+        }
+        findings.extend(stream.diagnostics().iter().map(Finding::from_diagnostic));
+    }
+    if args.errors_only {
+        findings.retain(|f| f.severity() == Severity::Error);
+    }
+
+    let mut w = std::io::stdout().lock();
+    match args.format {
+        Format::Text => {
+            for finding in &findings {
+                writeln!(w, "{}", finding)?;
+            }
+        }
+        Format::Json => {
+            serde_json::to_writer(&mut w, &findings)?;
+            writeln!(w)?;
+        }
+    }
+
+    match findings
+        .iter()
+        .filter(|f| f.severity() == Severity::Error)
+        .count()
+    {
+        0 => Ok(()),
+        n => Err(Error::Invalid(n)),
+    }
+}
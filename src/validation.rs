@@ -0,0 +1,381 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    atcf, geo,
+    hurdat2::{Diagnostic, Indicator, Resolution, Status, Storm, TrackEntry},
+};
+
+// MAX_SPEED is the default fastest plausible motion between two fixes, in
+// knots. Even accelerating extratropical systems rarely exceed it.
+const MAX_SPEED: f64 = 70.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn to_str(&self) -> &str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+impl std::fmt::Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
+// Check identifies the rule that produced a finding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    // Parse covers records that could not be parsed as written.
+    Parse,
+    EntryCount,
+    TimeOrder,
+    Coordinates,
+    Jump,
+    StatusWind,
+    MissingPressure,
+    Radii,
+}
+
+impl Check {
+    pub fn to_str(&self) -> &str {
+        match self {
+            Check::Parse => "parse",
+            Check::EntryCount => "entry_count",
+            Check::TimeOrder => "time_order",
+            Check::Coordinates => "coordinates",
+            Check::Jump => "jump",
+            Check::StatusWind => "status_wind",
+            Check::MissingPressure => "missing_pressure",
+            Check::Radii => "radii",
+        }
+    }
+}
+
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.to_str())
+    }
+}
+
+// Finding is a single problem found in the data.
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    severity: Severity,
+    check: Check,
+    storm: Option<atcf::Id>,
+    time: Option<DateTime<Utc>>,
+    line: Option<u64>,
+    message: String,
+}
+
+impl Finding {
+    fn new(
+        severity: Severity,
+        check: Check,
+        storm: &Storm,
+        entry: Option<&TrackEntry>,
+        message: String,
+    ) -> Finding {
+        Finding {
+            severity,
+            check,
+            storm: Some(storm.id().clone()),
+            time: entry.map(|e| e.time()),
+            line: None,
+            message,
+        }
+    }
+
+    // from_diagnostic converts a problem reported while leniently parsing a
+    // file into a finding. A header whose entry count doesn't match the
+    // number of track entries is always an error, other repairs are only
+    // warnings.
+    pub fn from_diagnostic(d: &Diagnostic) -> Finding {
+        let e = d.error();
+        let (severity, check) = match (e.field(), d.resolution()) {
+            ("num_track_entries", _) => (Severity::Error, Check::EntryCount),
+            (_, Resolution::Repaired) => (Severity::Warning, Check::Parse),
+            _ => (Severity::Error, Check::Parse),
+        };
+        Finding {
+            severity,
+            check,
+            storm: e.storm().cloned(),
+            time: None,
+            line: Some(e.line()),
+            message: format!("{}: {} {:?}", e.message(), e.field(), e.value()),
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn check(&self) -> Check {
+        self.check
+    }
+
+    pub fn storm(&self) -> Option<&atcf::Id> {
+        self.storm.as_ref()
+    }
+
+    pub fn time(&self) -> Option<DateTime<Utc>> {
+        self.time
+    }
+
+    pub fn line(&self) -> Option<u64> {
+        self.line
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: [{}]", self.severity, self.check)?;
+        if let Some(storm) = &self.storm {
+            write!(f, " {}", storm)?;
+        }
+        if let Some(time) = self.time {
+            write!(f, " {}", time.format("%Y-%m-%d %H:%MZ"))?;
+        }
+        if let Some(line) = self.line {
+            write!(f, " line {}", line)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+// Validator runs semantic consistency checks over parsed storms.
+#[derive(Debug, Clone)]
+pub struct Validator {
+    max_speed: f64,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Validator {
+            max_speed: MAX_SPEED,
+        }
+    }
+}
+
+impl Validator {
+    // with_max_speed sets the speed, in knots, above which motion between two
+    // fixes is reported as an implausible jump.
+    pub fn with_max_speed(mut self, kts: f64) -> Self {
+        self.max_speed = kts;
+        self
+    }
+
+    pub fn validate(&self, storm: &Storm) -> Vec<Finding> {
+        let mut findings = vec![];
+        let mut report = |severity, check, entry, message| {
+            findings.push(Finding::new(severity, check, storm, entry, message))
+        };
+
+        for (i, e) in storm.track().iter().enumerate() {
+            let lat = e.location().lat();
+            let lng = e.location().lng();
+            if !(-90.0..=90.0).contains(&lat) || !(-180.0..=180.0).contains(&lng) {
+                report(
+                    Severity::Error,
+                    Check::Coordinates,
+                    Some(e),
+                    format!("position {:.1},{:.1} is out of range", lat, lng),
+                );
+            }
+
+            if let Some(prev) = i.checked_sub(1).map(|j| &storm.track()[j]) {
+                let hours = (e.time() - prev.time()).num_seconds() as f64 / 3600.0;
+                if hours < 0.0 {
+                    report(
+                        Severity::Error,
+                        Check::TimeOrder,
+                        Some(e),
+                        format!("entry is earlier than the one before it at {}", prev.time()),
+                    );
+                } else if hours == 0.0 {
+                    report(
+                        Severity::Error,
+                        Check::TimeOrder,
+                        Some(e),
+                        "duplicate entry time".to_owned(),
+                    );
+                } else {
+                    let nm = geo::Location::distance_between(prev.location(), e.location())
+                        .in_nautical_miles();
+                    if nm / hours > self.max_speed {
+                        report(
+                            Severity::Warning,
+                            Check::Jump,
+                            Some(e),
+                            format!(
+                                "moved {:.0} nm in {:.1} h ({:.0} kt)",
+                                nm,
+                                hours,
+                                nm / hours
+                            ),
+                        );
+                    }
+                }
+            }
+
+            let wind = e.max_sustained_wind();
+            let status_wind = match e.status() {
+                Status::Hurricane if wind < 64 => Some(Severity::Error),
+                Status::TropicalStorm | Status::SubtropicalStorm if wind < 34 => {
+                    Some(Severity::Warning)
+                }
+                Status::TropicalStorm | Status::SubtropicalStorm if wind >= 64 => {
+                    Some(Severity::Warning)
+                }
+                Status::TropicalDepression | Status::SubtropicalDepression if wind >= 34 => {
+                    Some(Severity::Warning)
+                }
+                _ => None,
+            };
+            if let Some(severity) = status_wind {
+                report(
+                    severity,
+                    Check::StatusWind,
+                    Some(e),
+                    format!("status {} with {} kt winds", e.status().to_str(), wind),
+                );
+            }
+
+            if matches!(
+                e.indicator(),
+                Some(Indicator::MinCentralPressure | Indicator::IntensityPeak)
+            ) && e.min_pressure().is_none()
+            {
+                report(
+                    Severity::Error,
+                    Check::MissingPressure,
+                    Some(e),
+                    format!(
+                        "no pressure at an entry with indicator {}",
+                        e.indicator().unwrap()
+                    ),
+                );
+            }
+
+            for message in radii_problems(e) {
+                report(Severity::Warning, Check::Radii, Some(e), message);
+            }
+        }
+
+        findings
+    }
+}
+
+fn radii_problems(e: &TrackEntry) -> Vec<String> {
+    let mut problems = vec![];
+    let radii = |kts| {
+        let r = e.wind_radii(kts).unwrap();
+        [r.ne(), r.se(), r.sw(), r.nw()].map(|q| q.map_or(0.0, |d| d.in_nautical_miles()))
+    };
+    let (r34, r50, r64) = (radii(34), radii(50), radii(64));
+    let present = |r: &[f64; 4]| r.iter().any(|&v| v > 0.0);
+
+    let depression = matches!(
+        e.status(),
+        Status::TropicalDepression
+            | Status::SubtropicalDepression
+            | Status::Low
+            | Status::TropicalWave
+            | Status::Disturbance
+    );
+    if depression && present(&r34) {
+        problems.push(format!(
+            "34 kt wind radii present with status {}",
+            e.status().to_str()
+        ));
+    }
+
+    let wind = e.max_sustained_wind();
+    for (kts, r) in [(34, &r34), (50, &r50), (64, &r64)] {
+        if wind < kts && present(r) {
+            problems.push(format!(
+                "{} kt wind radii present with {} kt winds",
+                kts, wind
+            ));
+        }
+    }
+
+    for (inner, outer, kts) in [(&r50, &r34, 50), (&r64, &r50, 64)] {
+        if inner.iter().zip(outer.iter()).any(|(i, o)| i > o) {
+            problems.push(format!(
+                "{} kt wind radii extend beyond the next weaker radii",
+                kts
+            ));
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Check, Finding, Severity, Validator};
+    use crate::hurdat2::{Hurdat2Reader, ParseMode};
+
+    const SAMPLE: &str = "\
+AL012020,             ARTHUR,      5,
+20200901, 0000,  , TD, 20.0N,  60.0W,  40, 1000,   30,    0,    0,    0,    0,    0,    0,    0,    0,    0,    0,    0, -999,
+20200901, 0600,  , HU, 20.5N,  61.0W,  60,  998,   60,   60,   60,   60,   70,    0,    0,    0,    0,    0,    0,    0, -999,
+20200901, 0600,  , TS, 20.5N,  61.0W,  60,  998, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200901, 1200, P, TS, 30.0N,  61.0W,  60, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+20200901, 1800,  , TS, 30.5N,  61.5W,  60,  990, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+AL022020,             BERTHA,      3,
+20200905, 0000,  , TD, 25.0N,  70.0W,  30, 1008, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999, -999,
+";
+
+    #[test]
+    fn validate() {
+        let mut reader = Hurdat2Reader::new(SAMPLE.as_bytes(), ParseMode::Lenient);
+        let storms = reader.by_ref().collect::<Result<Vec<_>, _>>().unwrap();
+
+        let findings = Validator::default().validate(&storms[0]);
+        let checks = findings
+            .iter()
+            .map(|f| (f.check(), f.severity()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            checks,
+            vec![
+                (Check::StatusWind, Severity::Warning),
+                (Check::Radii, Severity::Warning),
+                (Check::StatusWind, Severity::Error),
+                (Check::Radii, Severity::Warning),
+                (Check::TimeOrder, Severity::Error),
+                (Check::Jump, Severity::Warning),
+                (Check::MissingPressure, Severity::Error),
+            ]
+        );
+        assert!(findings[4]
+            .to_string()
+            .contains("AL012020 2020-09-01 06:00Z"));
+        assert!(Validator::default().validate(&storms[1]).is_empty());
+
+        let findings = reader
+            .diagnostics()
+            .iter()
+            .map(Finding::from_diagnostic)
+            .collect::<Vec<_>>();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].check(), Check::EntryCount);
+    }
+}
@@ -0,0 +1,272 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+use crate::{
+    atcf,
+    forecast::Forecast,
+    geo,
+    hurdat2::{Status, Storm},
+};
+
+// Sample is the error of one forecast point, verified against the best track
+// entry at the time it was valid for.
+#[derive(Debug, Clone, Serialize)]
+pub struct Sample {
+    storm: atcf::Id,
+    technique: String,
+    init: DateTime<Utc>,
+    tau: i32,
+    track_error: f64,
+    intensity_error: Option<i32>,
+}
+
+impl Sample {
+    pub fn storm(&self) -> &atcf::Id {
+        &self.storm
+    }
+
+    pub fn technique(&self) -> &str {
+        &self.technique
+    }
+
+    pub fn init(&self) -> DateTime<Utc> {
+        self.init
+    }
+
+    pub fn tau(&self) -> i32 {
+        self.tau
+    }
+
+    // track_error is the great circle distance between the forecast and best
+    // track positions, in nm.
+    pub fn track_error(&self) -> f64 {
+        self.track_error
+    }
+
+    // intensity_error is the forecast wind minus the best track wind, in kt.
+    // It is None when the aid doesn't forecast intensity.
+    pub fn intensity_error(&self) -> Option<i32> {
+        self.intensity_error
+    }
+}
+
+// verify computes the error of each point of forecasts against the best
+// tracks in storms. As in the NHC's verification, a point is only verified
+// when the storm was a tropical or subtropical cyclone at both the initial
+// and the valid time, and the valid time is one of the best track's entries.
+pub fn verify(forecasts: &[Forecast], storms: &[Storm]) -> Vec<Sample> {
+    let storms = storms
+        .iter()
+        .map(|s| (s.id(), s))
+        .collect::<HashMap<_, _>>();
+    let mut samples = vec![];
+    for forecast in forecasts {
+        let storm = match storms.get(forecast.storm()) {
+            Some(storm) => storm,
+            None => continue,
+        };
+        let entry_at = |t: DateTime<Utc>| {
+            storm
+                .track()
+                .iter()
+                .find(|e| e.time() == t && is_cyclone(e.status()))
+        };
+        if entry_at(forecast.init()).is_none() {
+            continue;
+        }
+        for point in forecast.points() {
+            let best = match entry_at(point.valid_at(forecast.init())) {
+                Some(best) => best,
+                None => continue,
+            };
+            samples.push(Sample {
+                storm: forecast.storm().clone(),
+                technique: forecast.technique().to_owned(),
+                init: forecast.init(),
+                tau: point.tau(),
+                track_error: geo::Location::distance_between(point.location(), best.location())
+                    .in_nautical_miles(),
+                intensity_error: point.max_wind().map(|w| w - best.max_sustained_wind()),
+            });
+        }
+    }
+    samples
+}
+
+fn is_cyclone(status: Status) -> bool {
+    matches!(
+        status,
+        Status::TropicalDepression
+            | Status::TropicalStorm
+            | Status::Hurricane
+            | Status::SubtropicalDepression
+            | Status::SubtropicalStorm
+    )
+}
+
+// Summary aggregates the samples of a technique at one lead time, optionally
+// for a single season.
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    technique: String,
+    season: Option<i32>,
+    tau: i32,
+    count: usize,
+    track_error: f64,
+    intensity_count: usize,
+    intensity_error: Option<f64>,
+    intensity_bias: Option<f64>,
+}
+
+impl Summary {
+    // from_samples summarizes samples by technique and lead time, and also
+    // by season if by_season is set. The summaries are ordered by technique,
+    // season and lead time.
+    pub fn from_samples(samples: &[Sample], by_season: bool) -> Vec<Summary> {
+        let mut groups: BTreeMap<(&str, Option<i32>, i32), Vec<&Sample>> = BTreeMap::new();
+        for s in samples {
+            let season = if by_season {
+                Some(s.storm.year())
+            } else {
+                None
+            };
+            groups
+                .entry((s.technique.as_str(), season, s.tau))
+                .or_default()
+                .push(s);
+        }
+        groups
+            .into_iter()
+            .map(|((technique, season, tau), samples)| {
+                let intensity = samples
+                    .iter()
+                    .filter_map(|s| s.intensity_error)
+                    .map(|e| e as f64)
+                    .collect::<Vec<_>>();
+                let mean = |v: &[f64]| {
+                    if v.is_empty() {
+                        None
+                    } else {
+                        Some(v.iter().sum::<f64>() / v.len() as f64)
+                    }
+                };
+                Summary {
+                    technique: technique.to_owned(),
+                    season,
+                    tau,
+                    count: samples.len(),
+                    track_error: samples.iter().map(|s| s.track_error).sum::<f64>()
+                        / samples.len() as f64,
+                    intensity_count: intensity.len(),
+                    intensity_error: mean(&intensity.iter().map(|e| e.abs()).collect::<Vec<_>>()),
+                    intensity_bias: mean(&intensity),
+                }
+            })
+            .collect()
+    }
+
+    pub fn technique(&self) -> &str {
+        &self.technique
+    }
+
+    pub fn season(&self) -> Option<i32> {
+        self.season
+    }
+
+    pub fn tau(&self) -> i32 {
+        self.tau
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    // track_error is the mean track error, in nm.
+    pub fn track_error(&self) -> f64 {
+        self.track_error
+    }
+
+    // intensity_count is the number of samples with an intensity forecast.
+    pub fn intensity_count(&self) -> usize {
+        self.intensity_count
+    }
+
+    // intensity_error is the mean absolute intensity error, in kt.
+    pub fn intensity_error(&self) -> Option<f64> {
+        self.intensity_error
+    }
+
+    // intensity_bias is the mean intensity error, in kt. It is positive when
+    // the technique forecasts storms to be stronger than they were.
+    pub fn intensity_bias(&self) -> Option<f64> {
+        self.intensity_bias
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{verify, Summary};
+    use crate::{atcf::BestTrack, forecast::Forecast};
+
+    const B_DECK: &str = "\
+AL, 10, 2023082900,   , BEST,   0, 240N,  851W,  70,  985, HU,  34, NEQ,  100,  90,   60,   80, 1008,  200,  20,  85,   0,   L,   0,    ,   0,   0,     IDALIA, D,
+AL, 10, 2023082912,   , BEST,   0, 260N,  848W,  85,  972, HU,  34, NEQ,  120, 100,   60,   90, 1008,  200,  20, 105,   0,   L,   0,    ,   0,   0,     IDALIA, D,
+AL, 10, 2023083000,   , BEST,   0, 288N,  838W, 110,  942, HU,  34, NEQ,  130,  130,   70,   90, 1010,  240,  15, 135,  30,   L,   0,    , 355,  14,     IDALIA, D,
+";
+
+    const A_DECK: &str = "\
+AL, 10, 2023082900, 03, CARQ, -12, 220N,  852W,  60,  990, TS,  34, NEQ,  100,   90,   60,   80,
+AL, 10, 2023082900, 03, CARQ,   0, 240N,  851W,  70,  985, HU,  34, NEQ,  100,   90,   60,   80,
+AL, 10, 2023082900, 03, OFCL,   0, 240N,  851W,  70,  985, HU,  34, NEQ,  100,   90,   60,   80,
+AL, 10, 2023082900, 03, OFCL,  12, 262N,  850W,  85,    0, HU,  34, NEQ,  110,  100,   60,   80,
+AL, 10, 2023082900, 03, OFCL,  12, 262N,  850W,  85,    0, HU,  50, NEQ,   50,   40,   30,   40,
+AL, 10, 2023082900, 03, OFCL,  24, 290N,  840W, 100,    0, HU,  34, NEQ,  120,  110,   70,   90,
+AL, 10, 2023082900, 03, TVCN,  12, 258N,  846W,   0,    0,   ,   0,    ,    0,    0,    0,    0,
+AL, 10, 2023082900, 03, TVCN,  24, 284N,  834W,   0,    0,   ,   0,    ,    0,    0,    0,    0,
+AL, 10, 2023082912, 03, OFCL,  12, 290N,  838W, 100,    0, HU,  34, NEQ,  120,  110,   70,   90,
+AL, 10, 2023082912, 03, OFCL,  36, 330N,  800W,  60,    0, TS,  34, NEQ,  120,  110,   70,   90,
+";
+
+    #[test]
+    fn verify_forecasts() {
+        let storm = BestTrack::read(B_DECK.as_bytes()).unwrap().to_storm();
+        let forecasts = Forecast::read_a_deck(A_DECK.as_bytes()).unwrap();
+        assert_eq!(forecasts.len(), 4);
+        assert_eq!(forecasts[1].technique(), "OFCL");
+        assert_eq!(forecasts[1].points().len(), 3);
+
+        let samples = verify(&forecasts, &[storm]);
+        let ofcl = samples
+            .iter()
+            .filter(|s| s.technique() == "OFCL")
+            .map(|s| (s.tau(), s.track_error().round(), s.intensity_error()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ofcl,
+            vec![
+                (0, 0.0, Some(0)),
+                (12, 16.0, Some(0)),
+                (24, 16.0, Some(-10)),
+                (12, 12.0, Some(-10)),
+            ]
+        );
+
+        let summaries = Summary::from_samples(&samples, false);
+        let s = summaries
+            .iter()
+            .find(|s| s.technique() == "OFCL" && s.tau() == 12)
+            .unwrap();
+        assert_eq!(s.count(), 2);
+        assert_eq!(s.intensity_error(), Some(5.0));
+        assert_eq!(s.intensity_bias(), Some(-5.0));
+        let s = summaries
+            .iter()
+            .find(|s| s.technique() == "TVCN" && s.tau() == 24)
+            .unwrap();
+        assert_eq!(s.count(), 1);
+        assert_eq!(s.intensity_count(), 0);
+        assert_eq!(s.intensity_bias(), None);
+    }
+}
@@ -0,0 +1,99 @@
+use std::{io::Write, path::PathBuf};
+
+use tokio::fs;
+
+use crate::{
+    forecast::Forecast,
+    source::StormSource,
+    verification::{self, Summary},
+    DataDir, Error,
+};
+
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    #[clap(
+        required = true,
+        help = "ATCF a-deck files holding the forecasts to verify"
+    )]
+    a_decks: Vec<PathBuf>,
+
+    #[clap(flatten)]
+    source: StormSource,
+
+    #[clap(
+        long = "technique",
+        help = "only verify forecasts from this technique, e.g. OFCL, may be repeated"
+    )]
+    techniques: Vec<String>,
+
+    #[clap(long, help = "summarize each season separately")]
+    by_season: bool,
+
+    #[clap(long, value_enum, default_value_t = Format::Table, help = "output format")]
+    format: Format,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum Format {
+    Table,
+    Json,
+}
+
+pub async fn run(dir: &DataDir<'_>, args: &Args) -> Result<(), Error> {
+    let mut forecasts = vec![];
+    for path in &args.a_decks {
+        let contents = fs::read(path).await?;
+        forecasts.extend(Forecast::read_a_deck(contents.as_slice())?);
+    }
+    if !args.techniques.is_empty() {
+        forecasts.retain(|f| {
+            args.techniques
+                .iter()
+                .any(|t| t.eq_ignore_ascii_case(f.technique()))
+        });
+    }
+
+    let ids = forecasts
+        .iter()
+        .map(|f| f.storm().clone())
+        .collect::<std::collections::HashSet<_>>();
+    let storms = args.source.load(dir, |s| ids.contains(s.id())).await?;
+
+    let samples = verification::verify(&forecasts, &storms);
+    let summaries = Summary::from_samples(&samples, args.by_season);
+
+    let mut w = std::io::stdout().lock();
+    match args.format {
+        Format::Table => write_table(&mut w, &summaries)?,
+        Format::Json => {
+            serde_json::to_writer(&mut w, &summaries)?;
+            writeln!(w)?;
+        }
+    }
+    Ok(())
+}
+
+fn write_table<W: Write>(w: &mut W, summaries: &[Summary]) -> Result<(), Error> {
+    writeln!(
+        w,
+        "{:<6} {:>6} {:>4} {:>5} {:>8} {:>5} {:>6} {:>6}",
+        "TECH", "SEASON", "TAU", "N", "TRACK", "N_INT", "MAE", "BIAS"
+    )?;
+    let kts = |v: Option<f64>| v.map_or_else(|| "-".to_owned(), |v| format!("{:.1}", v));
+    for s in summaries {
+        writeln!(
+            w,
+            "{:<6} {:>6} {:>4} {:>5} {:>8.1} {:>5} {:>6} {:>6}",
+            s.technique(),
+            s.season()
+                .map_or_else(|| "all".to_owned(), |y| y.to_string()),
+            s.tau(),
+            s.count(),
+            s.track_error(),
+            s.intensity_count(),
+            kts(s.intensity_error()),
+            kts(s.intensity_bias()),
+        )?;
+    }
+    Ok(())
+}